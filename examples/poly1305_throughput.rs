@@ -0,0 +1,60 @@
+//! Throughput of the Poly1305 backends on a 1 MiB message:
+//! cargo run --release --example poly1305_throughput
+//!
+//! On x86-64 the cycles come from the time stamp counter, which ticks at the
+//! nominal clock of the CPU, elsewhere only the time is printed.
+
+use std::{hint::black_box, time::Instant};
+
+use badcrypto::mac::{
+    OneTimeAuthenticator, Poly1305, Poly1305Backend, Poly1305Radix26, Poly1305Radix44,
+    Poly1305Radix44x4, Poly1305Radix64,
+};
+
+const ROUNDS: usize = 256;
+
+#[cfg(target_arch = "x86_64")]
+fn cycles() -> Option<u64> {
+    // SAFETY: rdtsc is available on every x86-64 CPU
+    Some(unsafe { std::arch::x86_64::_rdtsc() })
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn cycles() -> Option<u64> {
+    None
+}
+
+fn run<B: Poly1305Backend>(name: &str, message: &[u8]) {
+    let bytes = (ROUNDS * message.len()) as f64;
+
+    // the best of several runs, to skip over noise from the rest of the system
+    let mut best_ns = f64::MAX;
+    let mut best_cycles = f64::MAX;
+    for _ in 0..5 {
+        let start = Instant::now();
+        let start_cycles = cycles();
+        for _ in 0..ROUNDS {
+            let mut mac = Poly1305::<B>::with_backend(&[7; 32]);
+            mac.update(black_box(message));
+            black_box(mac.finalize());
+        }
+        if let (Some(start), Some(end)) = (start_cycles, cycles()) {
+            best_cycles = best_cycles.min((end - start) as f64 / bytes);
+        }
+        best_ns = best_ns.min(start.elapsed().as_nanos() as f64 / bytes);
+    }
+
+    if best_cycles < f64::MAX {
+        println!("{name:<22} {best_cycles:.2} cycles/byte  {best_ns:.3} ns/byte");
+    } else {
+        println!("{name:<22} {best_ns:.3} ns/byte");
+    }
+}
+
+fn main() {
+    let message = vec![0xa5; 1 << 20];
+    run::<Poly1305Radix64>("radix 2^64", &message);
+    run::<Poly1305Radix26>("radix 2^26", &message);
+    run::<Poly1305Radix44>("radix 2^44", &message);
+    run::<Poly1305Radix44x4>("radix 2^44, 4 blocks", &message);
+}
@@ -27,7 +27,7 @@ pub(crate) fn borrowing_sub(lhs: u64, rhs: u64, borrow: u64) -> (u64, u64) {
 #[inline]
 pub(crate) fn widening_mul(lhs: u64, rhs: u64) -> (u64, u64) {
     let temp = (lhs as u128) * (rhs as u128);
-    return ((temp & 0xFFFF_FFFF_FFFF_FFFF) as u64, (temp << 64) as u64);
+    return ((temp & 0xFFFF_FFFF_FFFF_FFFF) as u64, (temp >> 64) as u64);
 }
 
 // constant-time selector.
@@ -59,6 +59,13 @@ mod tests {
         assert_eq!(borrowing_sub(5, 10, 1), (u64::MAX - 5, 1));
     }
 
+    #[test]
+    fn test_mul() {
+        assert_eq!(widening_mul(3, 5), (15, 0));
+        assert_eq!(widening_mul(u64::MAX, u64::MAX), (1, u64::MAX - 1));
+        assert_eq!(widening_mul(1 << 63, 4), (0, 2));
+    }
+
     #[test]
     fn test_cts64() {
         assert_eq!(ct_select_64(10, 5, 0), 5);
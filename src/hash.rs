@@ -1,6 +1,64 @@
+mod blake2_parallel;
 mod blake2b;
+mod blake2s;
+mod blake2xb;
+mod blake3;
+mod dyn_hash;
+mod io;
+mod keccak;
+#[cfg(feature = "legacy")]
+mod md5;
+mod ripemd160;
+#[cfg(feature = "legacy")]
+mod sha1;
+mod sha2;
+mod sha3;
+mod sp800_185;
+#[cfg(test)]
+mod test_util;
 
-pub use blake2b::Blake2b;
+use std::fmt;
+
+pub use blake2_parallel::{Blake2Core, Blake2Parallel, Blake2bp, Blake2sp};
+pub use blake2b::{Blake2b, Blake2bParams};
+pub use blake2s::{Blake2s, Blake2sParams};
+pub use blake2xb::{Blake2xb, Blake2xbReader};
+pub use blake3::{Blake3, Blake3Reader};
+pub use dyn_hash::DynHash;
+pub use io::{HashWriter, hash_file, hash_reader};
+pub use keccak::{KeccakSponge, keccak_f1600};
+#[cfg(feature = "legacy")]
+pub use md5::Md5;
+pub use ripemd160::{Ripemd160, hash160};
+#[cfg(feature = "legacy")]
+pub use sha1::Sha1;
+pub use sha2::{Sha224, Sha256, Sha384, Sha512, Sha512_224, Sha512_256};
+pub use sha3::{
+    Keccak256, Sha3_224, Sha3_256, Sha3_384, Sha3_512, Shake128, Shake256, ShakeReader,
+};
+pub use sp800_185::{CShake, Kmac, ParallelHash, TupleHash};
+
+/// Why a hash refused to give a digest. Only SHA-1 (with the legacy feature)
+/// does, when it detects a collision attack in the message. Its digest()
+/// returns a safe hash instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestError {
+    /// a block of a known SHA-1 collision attack, at this byte offset
+    Collision { offset: u64 },
+}
+
+impl fmt::Display for DigestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Collision { offset } => write!(
+                f,
+                "SHA-1 collision attack detected in the block at byte {offset}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DigestError {}
 
 pub trait HashFunction {
     /// update message
@@ -9,4 +67,57 @@ pub trait HashFunction {
     fn digest(self) -> Vec<u8>;
     /// shortcut for init -> update -> digest with default settings
     fn hash(message: &[u8]) -> Vec<u8>;
+    /// like digest(), but with an error for input the hash refuses, see
+    /// DigestError
+    fn try_digest(self) -> Result<Vec<u8>, DigestError>
+    where
+        Self: Sized,
+    {
+        Ok(self.digest())
+    }
+}
+
+/// A hash function with a known block size and a bounded output, whose
+/// midstate can be cloned to hash a common prefix only once.
+pub trait FixedOutputHash: HashFunction + Clone {
+    /// bytes per compressed block
+    const BLOCK_SIZE: usize;
+    /// the largest output in bytes
+    const OUTPUT_SIZE: usize;
+
+    /// output length of this instance, at most OUTPUT_SIZE
+    fn output_size(&self) -> usize {
+        Self::OUTPUT_SIZE
+    }
+
+    /// write the digest into out, which has to be output_size() bytes long
+    fn finalize_into(self, out: &mut [u8]);
+
+    /// like finalize_into(), but with an error for input the hash refuses,
+    /// see DigestError
+    fn try_finalize_into(self, out: &mut [u8]) -> Result<(), DigestError> {
+        self.finalize_into(out);
+        Ok(())
+    }
+
+    /// go back to the initial state, keeping the settings (and key)
+    fn reset(&mut self);
+
+    /// the digest as an array without allocating, panics unless N is
+    /// output_size()
+    fn finalize_array<const N: usize>(self) -> [u8; N] {
+        let output_size = self.output_size();
+        if N != output_size {
+            panic!("finalize_array length does not match the hash! {N} != {output_size}");
+        }
+        let mut result = [0; N];
+        self.finalize_into(&mut result);
+        result
+    }
+}
+
+/// Reads the output stream of an extendable-output function (XOF).
+pub trait XofReader {
+    /// fill buf with the next bytes of the output stream
+    fn read(&mut self, buf: &mut [u8]);
 }
@@ -0,0 +1,331 @@
+use std::thread;
+
+use super::{Blake2b, Blake2bParams, Blake2s, Blake2sParams, HashFunction};
+
+/// The Blake2 variant under a parallel tree mode: it supplies the nodes of
+/// the tree, Blake2Parallel spreads the message over the leaves.
+pub trait Blake2Core: HashFunction + Send {
+    /// The number of leaves, 4 for Blake2bp and 8 for Blake2sp.
+    const DEGREE: usize;
+    const BLOCK_BYTES: usize;
+    /// The largest hash length, which is also the length of the leaf hashes.
+    const OUT_BYTES: usize;
+
+    /// A node of the depth 2 tree. The leaves (node depth 0) absorb the key,
+    /// the root (node depth 1) only carries the key length.
+    fn tree_node(
+        hashlen: usize,
+        key: &[u8],
+        node_offset: u64,
+        node_depth: u8,
+        last_node: bool,
+    ) -> Self;
+
+    /// The full length hash of a leaf, which goes into the root.
+    fn finalize_leaf(self) -> Vec<u8>;
+}
+
+impl Blake2Core for Blake2b {
+    const DEGREE: usize = 4;
+    const BLOCK_BYTES: usize = 128;
+    const OUT_BYTES: usize = 64;
+
+    fn tree_node(
+        hashlen: usize,
+        key: &[u8],
+        node_offset: u64,
+        node_depth: u8,
+        last_node: bool,
+    ) -> Self {
+        let params = Blake2bParams::new()
+            .hashlen(hashlen)
+            .key(key)
+            .fanout(Self::DEGREE as u8)
+            .depth(2)
+            .node_offset(node_offset)
+            .node_depth(node_depth)
+            .inner_length(Self::OUT_BYTES)
+            .last_node(last_node);
+
+        if node_depth == 0 {
+            params.build()
+        } else {
+            params.build_inner_node()
+        }
+    }
+
+    fn finalize_leaf(self) -> Vec<u8> {
+        self.finalize().to_vec()
+    }
+}
+
+impl Blake2Core for Blake2s {
+    const DEGREE: usize = 8;
+    const BLOCK_BYTES: usize = 64;
+    const OUT_BYTES: usize = 32;
+
+    fn tree_node(
+        hashlen: usize,
+        key: &[u8],
+        node_offset: u64,
+        node_depth: u8,
+        last_node: bool,
+    ) -> Self {
+        let params = Blake2sParams::new()
+            .hashlen(hashlen)
+            .key(key)
+            .fanout(Self::DEGREE as u8)
+            .depth(2)
+            .node_offset(node_offset)
+            .node_depth(node_depth)
+            .inner_length(Self::OUT_BYTES)
+            .last_node(last_node);
+
+        if node_depth == 0 {
+            params.build()
+        } else {
+            params.build_inner_node()
+        }
+    }
+
+    fn finalize_leaf(self) -> Vec<u8> {
+        self.finalize().to_vec()
+    }
+}
+
+/// The parallel tree hashing modes of Blake2 (section 2.10 of the BLAKE2
+/// spec): leaf i gets every DEGREE-th block of the message, starting at
+/// block i, and the root hashes the leaf hashes. They produce different
+/// hashes than the underlying Blake2b or Blake2s.
+pub struct Blake2Parallel<C: Blake2Core> {
+    leaves: Vec<C>,
+    root: C,
+    // position within the current stripe of DEGREE blocks
+    offset: usize,
+    threaded: bool,
+}
+
+/// Blake2bp, the 4-way parallel tree hashing mode of Blake2b.
+pub type Blake2bp = Blake2Parallel<Blake2b>;
+
+/// Blake2sp, the 8-way parallel tree hashing mode of Blake2s.
+pub type Blake2sp = Blake2Parallel<Blake2s>;
+
+impl<C: Blake2Core> Blake2Parallel<C> {
+    const STRIPE_BYTES: usize = C::DEGREE * C::BLOCK_BYTES;
+
+    /// Initialise a parallel Blake2 hash function.
+    /// hashlen is the length of the produced hash in bytes, up to 64 for
+    /// Blake2bp and from 8 to 32 for Blake2sp.
+    pub fn new(hashlen: usize) -> Self {
+        Self::new_keyed(hashlen, &[])
+    }
+
+    /// Initialise a keyed parallel Blake2 hash function (a MAC).
+    /// The key can be up to 64 bytes long for Blake2bp, 32 for Blake2sp.
+    pub fn new_keyed(hashlen: usize, key: &[u8]) -> Self {
+        let leaves = (0..C::DEGREE)
+            .map(|i| C::tree_node(hashlen, key, i as u64, 0, i == C::DEGREE - 1))
+            .collect();
+        let root = C::tree_node(hashlen, key, 0, 1, true);
+
+        Self {
+            leaves,
+            root,
+            offset: 0,
+            threaded: false,
+        }
+    }
+
+    /// Hash the leaves on separate threads. The hash does not change, but
+    /// large updates get faster.
+    pub fn threaded(mut self, threaded: bool) -> Self {
+        self.threaded = threaded;
+        self
+    }
+
+    /// Hands the message to the leaves one block (or part of one) at a time.
+    fn update_sequential(&mut self, mut message: &[u8]) {
+        while !message.is_empty() {
+            let leaf = self.offset / C::BLOCK_BYTES;
+            let take = (C::BLOCK_BYTES - self.offset % C::BLOCK_BYTES).min(message.len());
+            self.leaves[leaf].update(&message[..take]);
+            self.offset = (self.offset + take) % Self::STRIPE_BYTES;
+            message = &message[take..];
+        }
+    }
+
+    /// Hashes leaf i's share of a run of whole stripes.
+    fn update_leaf(leaf: &mut C, i: usize, stripes: &[u8]) {
+        for stripe in stripes.chunks_exact(Self::STRIPE_BYTES) {
+            leaf.update(&stripe[i * C::BLOCK_BYTES..(i + 1) * C::BLOCK_BYTES]);
+        }
+    }
+}
+
+impl<C: Blake2Core> HashFunction for Blake2Parallel<C> {
+    fn update(&mut self, mut message: &[u8]) {
+        // finish the current stripe first, so the rest starts at leaf 0
+        if self.offset != 0 {
+            let take = (Self::STRIPE_BYTES - self.offset).min(message.len());
+            self.update_sequential(&message[..take]);
+            message = &message[take..];
+        }
+
+        let whole = message.len() - message.len() % Self::STRIPE_BYTES;
+        let (stripes, rest) = message.split_at(whole);
+
+        if self.threaded && !stripes.is_empty() {
+            thread::scope(|s| {
+                for (i, leaf) in self.leaves.iter_mut().enumerate() {
+                    s.spawn(move || Self::update_leaf(leaf, i, stripes));
+                }
+            });
+        } else {
+            for (i, leaf) in self.leaves.iter_mut().enumerate() {
+                Self::update_leaf(leaf, i, stripes);
+            }
+        }
+
+        self.update_sequential(rest);
+    }
+
+    fn digest(self) -> Vec<u8> {
+        let mut root = self.root;
+        for leaf in self.leaves {
+            root.update(&leaf.finalize_leaf());
+        }
+        root.digest()
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new(C::OUT_BYTES);
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::{Blake2bp, Blake2sp, HashFunction},
+        slice::u8_to_hexstr,
+    };
+
+    #[test]
+    fn basic_blake2bp() {
+        let res1 = u8_to_hexstr(&Blake2bp::hash(b""));
+        let exp1 = "b5ef811a8038f70b628fa8b294daae7492b1ebe343a80eaabbf1f6ae664dd67b9d90b0120791eab81dc96985f28849f6a305186a85501b405114bfa678df9380";
+        assert_eq!(res1, exp1);
+
+        let res2 = u8_to_hexstr(&Blake2bp::hash(b"foo"));
+        let exp2 = "8ca9ccee7946afcb686fe7556628b5ba1bf9a691da37ca58cd049354d99f37042c007427e5f219b9ab5063707ec6823872dee413ee014b4d02f2ebb6abb5f643";
+        assert_eq!(res2, exp2);
+
+        let message: Vec<u8> = (0..3000).map(|i| (i % 251) as u8).collect();
+        let res3 = u8_to_hexstr(&Blake2bp::hash(&message));
+        let exp3 = "e7e2e0eeabc58ca7228a4fa20580a6e34ecb754ff2cc23123a6547646e88dce7e03573c2140d272dc6a4c4994e2a7242c3358787b2615b8f2cf1b62561c5e6cb";
+        assert_eq!(res3, exp3);
+    }
+
+    #[test]
+    fn basic_blake2sp() {
+        let res1 = u8_to_hexstr(&Blake2sp::hash(b""));
+        let exp1 = "dd0e891776933f43c7d032b08a917e25741f8aa9a12c12e1cac8801500f2ca4f";
+        assert_eq!(res1, exp1);
+
+        let res2 = u8_to_hexstr(&Blake2sp::hash(b"foo"));
+        let exp2 = "050dc5786037ea72cb9ed9d0324afcab03c97ec02e8c47368fc5dfb4cf49d8c9";
+        assert_eq!(res2, exp2);
+
+        let message: Vec<u8> = (0..3000).map(|i| (i % 251) as u8).collect();
+        let res3 = u8_to_hexstr(&Blake2sp::hash(&message));
+        let exp3 = "99f49255642a0e14b148b7489438fbf0cd86e011579855cdfe813c7bb29b7816";
+        assert_eq!(res3, exp3);
+    }
+
+    #[test]
+    fn keyed_blake2bp() {
+        let mut hasher = Blake2bp::new_keyed(16, b"The Magic Words are Squeamish Ossifrage");
+        hasher.update(b"foo");
+        hasher.update(b"bar");
+        hasher.update(b"baz");
+        let res = u8_to_hexstr(&hasher.digest());
+        let exp = "e69c7d2c42a5ac14948772231c68c552";
+        assert_eq!(res, exp);
+
+        // the first and last entries of blake2bp-kat.txt, key = 00 01 02 .. 3f
+        let key: Vec<u8> = (0..64).collect();
+        let message: Vec<u8> = (0..255).collect();
+        let cases = [
+            (
+                &message[..0],
+                "9d9461073e4eb640a255357b839f394b838c6ff57c9b686a3f76107c1066728f3c9956bd785cbc3bf79dc2ab578c5a0c063b9d9c405848de1dbe821cd05c940a",
+            ),
+            (
+                &message[..],
+                "96fbcbb60bd313b8845033e5bc058a38027438572d7e7957f3684f6268aadd3ad08d21767ed6878685331ba98571487e12470aad669326716e46667f69f8d7e8",
+            ),
+        ];
+        for (message, exp) in cases {
+            let mut hasher = Blake2bp::new_keyed(64, &key);
+            hasher.update(message);
+            assert_eq!(u8_to_hexstr(&hasher.digest()), exp);
+        }
+    }
+
+    #[test]
+    fn keyed_blake2sp() {
+        let mut hasher = Blake2sp::new_keyed(16, b"Squeamish Ossifrage");
+        hasher.update(b"foo");
+        hasher.update(b"bar");
+        hasher.update(b"baz");
+        let res = u8_to_hexstr(&hasher.digest());
+        let exp = "9a604f1653c25063debb3aeea79671c0";
+        assert_eq!(res, exp);
+
+        // the first and last entries of blake2sp-kat.txt, key = 00 01 02 .. 1f
+        let key: Vec<u8> = (0..32).collect();
+        let message: Vec<u8> = (0..255).collect();
+        let cases = [
+            (
+                &message[..0],
+                "715cb13895aeb678f6124160bff21465b30f4f6874193fc851b4621043f09cc6",
+            ),
+            (
+                &message[..],
+                "0c8a36597d7461c63a94732821c941856c668376606c86a52de0ee4104c615db",
+            ),
+        ];
+        for (message, exp) in cases {
+            let mut hasher = Blake2sp::new_keyed(32, &key);
+            hasher.update(message);
+            assert_eq!(u8_to_hexstr(&hasher.digest()), exp);
+        }
+    }
+
+    #[test]
+    fn blake2p_threaded_matches_sequential() {
+        let message: Vec<u8> = (0..10_000).map(|i| (i % 251) as u8).collect();
+        let expected_b = Blake2bp::hash(&message);
+        let expected_s = Blake2sp::hash(&message);
+
+        for split in [1, 64, 100, 128, 512, 513, 4000] {
+            let mut sequential_b = Blake2bp::new(64);
+            let mut threaded_b = Blake2bp::new(64).threaded(true);
+            let mut sequential_s = Blake2sp::new(32);
+            let mut threaded_s = Blake2sp::new(32).threaded(true);
+            for chunk in message.chunks(split) {
+                sequential_b.update(chunk);
+                threaded_b.update(chunk);
+                sequential_s.update(chunk);
+                threaded_s.update(chunk);
+            }
+            let msg = format!("split into chunks of {split}");
+            assert_eq!(sequential_b.digest(), expected_b, "{msg}");
+            assert_eq!(threaded_b.digest(), expected_b, "{msg}");
+            assert_eq!(sequential_s.digest(), expected_s, "{msg}");
+            assert_eq!(threaded_s.digest(), expected_s, "{msg}");
+        }
+    }
+}
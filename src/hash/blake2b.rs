@@ -1,6 +1,6 @@
-use crate::slice::{pad_trailing, prepend, u8_to_hexstr};
+use crate::slice::u8_to_hexstr;
 
-use super::HashFunction;
+use super::{FixedOutputHash, HashFunction};
 
 const BLAKE2B_IV: [u64; 8] = [
     0x6a09e667f3bcc908, // Frac(sqrt(2))
@@ -13,7 +13,7 @@ const BLAKE2B_IV: [u64; 8] = [
     0x5be0cd19137e2179, // Frac(sqrt(19))
 ];
 
-const SIGMA: [[usize; 16]; 12] = [
+pub(super) const SIGMA: [[usize; 16]; 12] = [
     [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
     [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
     [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
@@ -28,14 +28,227 @@ const SIGMA: [[usize; 16]; 12] = [
     [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
 ];
 
-pub struct Blake2b<'a> {
+const BLAKE2B_BLOCK_BYTES: usize = 128;
+
+/// The Blake2b parameter block (section 2.5 of the BLAKE2 spec).
+/// The defaults describe sequential hashing with a 64 byte digest.
+#[derive(Clone)]
+pub struct Blake2bParams {
+    hashlen: u8,
+    key: [u8; 64],
+    keylen: u8,
+    salt: [u8; 16],
+    personal: [u8; 16],
+    fanout: u8,
+    depth: u8,
+    leaf_length: u32,
+    node_offset: u64,
+    node_depth: u8,
+    inner_length: u8,
+    last_node: bool,
+}
+
+impl Blake2bParams {
+    pub fn new() -> Self {
+        Self {
+            hashlen: 64,
+            key: [0; 64],
+            keylen: 0,
+            salt: [0; 16],
+            personal: [0; 16],
+            fanout: 1,
+            depth: 1,
+            leaf_length: 0,
+            node_offset: 0,
+            node_depth: 0,
+            inner_length: 0,
+            last_node: false,
+        }
+    }
+
+    /// Length of the produced hash in bytes, from 1 up to 64.
+    pub fn hashlen(mut self, hashlen: usize) -> Self {
+        if hashlen == 0 || hashlen > 64 {
+            panic!("Blake2b hash length not supported! {hashlen} (supported: 1 to 64)");
+        }
+        self.hashlen = hashlen as u8;
+        self
+    }
+
+    /// Key for MAC mode, up to 64 bytes. An empty key means unkeyed hashing.
+    pub fn key(mut self, key: &[u8]) -> Self {
+        if key.len() > 64 {
+            panic!(
+                "Blake2b key length greater than supported! {} > 64",
+                key.len()
+            );
+        }
+        self.key = [0; 64];
+        self.key[..key.len()].copy_from_slice(key);
+        self.keylen = key.len() as u8;
+        self
+    }
+
+    /// Salt of up to 16 bytes, zero padded if shorter.
+    pub fn salt(mut self, salt: &[u8]) -> Self {
+        if salt.len() > 16 {
+            panic!(
+                "Blake2b salt length greater than supported! {} > 16",
+                salt.len()
+            );
+        }
+        self.salt = [0; 16];
+        self.salt[..salt.len()].copy_from_slice(salt);
+        self
+    }
+
+    /// Personalization string of up to 16 bytes, zero padded if shorter.
+    /// Hashes with different personalizations are independent of each other,
+    /// which gives domain separation between protocols.
+    pub fn personal(mut self, personal: &[u8]) -> Self {
+        if personal.len() > 16 {
+            panic!(
+                "Blake2b personalization length greater than supported! {} > 16",
+                personal.len()
+            );
+        }
+        self.personal = [0; 16];
+        self.personal[..personal.len()].copy_from_slice(personal);
+        self
+    }
+
+    /// Maximal number of children of a tree node, 0 for unlimited.
+    pub fn fanout(mut self, fanout: u8) -> Self {
+        self.fanout = fanout;
+        self
+    }
+
+    /// Maximal depth of the tree, from 1 to 255 (255 for unlimited).
+    pub fn depth(mut self, depth: u8) -> Self {
+        if depth == 0 {
+            panic!("Blake2b tree depth must be at least 1!");
+        }
+        self.depth = depth;
+        self
+    }
+
+    /// BLAKE2X output nodes are the exception and use a depth of 0.
+    pub(super) fn xof_output_depth(mut self) -> Self {
+        self.depth = 0;
+        self
+    }
+
+    /// Maximal byte length of a leaf, 0 for unlimited.
+    pub fn leaf_length(mut self, leaf_length: u32) -> Self {
+        self.leaf_length = leaf_length;
+        self
+    }
+
+    /// Offset of the node within its level, starting at 0.
+    pub fn node_offset(mut self, node_offset: u64) -> Self {
+        self.node_offset = node_offset;
+        self
+    }
+
+    /// Depth of the node in the tree, 0 for leaves.
+    pub fn node_depth(mut self, node_depth: u8) -> Self {
+        self.node_depth = node_depth;
+        self
+    }
+
+    /// Length of the intermediate hashes fed to inner nodes, up to 64.
+    pub fn inner_length(mut self, inner_length: usize) -> Self {
+        if inner_length > 64 {
+            panic!("Blake2b inner length greater than supported! {inner_length} > 64");
+        }
+        self.inner_length = inner_length as u8;
+        self
+    }
+
+    /// Marks the node as the last (rightmost) one of its level.
+    pub fn last_node(mut self, last_node: bool) -> Self {
+        self.last_node = last_node;
+        self
+    }
+
+    /// The parameter block as the 8 words xored into the IV.
+    fn to_words(&self) -> [u64; 8] {
+        let le = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().expect("len 8"));
+
+        [
+            self.hashlen as u64
+                | (self.keylen as u64) << 8
+                | (self.fanout as u64) << 16
+                | (self.depth as u64) << 24
+                | (self.leaf_length as u64) << 32,
+            self.node_offset,
+            self.node_depth as u64 | (self.inner_length as u64) << 8,
+            0,
+            le(&self.salt[..8]),
+            le(&self.salt[8..]),
+            le(&self.personal[..8]),
+            le(&self.personal[8..]),
+        ]
+    }
+
+    /// Initialise a Blake2b hash function with these parameters.
+    pub fn build(&self) -> Blake2b {
+        let mut hasher = self.build_inner_node();
+
+        // if there was a key: it is padded to a full block and becomes the
+        // first block of the message
+        if self.keylen > 0 {
+            hasher.key = Some(self.key);
+            hasher.reset();
+        }
+
+        hasher
+    }
+
+    /// Nodes above the leaves of a tree carry the key length in their
+    /// parameter block, but never absorb the key itself.
+    pub(super) fn build_inner_node(&self) -> Blake2b {
+        let mut state = BLAKE2B_IV;
+        for (s, p) in state.iter_mut().zip(self.to_words()) {
+            *s ^= p;
+        }
+
+        Blake2b {
+            hashlen: self.hashlen as usize,
+            state,
+            buffer: [0; BLAKE2B_BLOCK_BYTES],
+            buflen: 0,
+            counter: 0,
+            last_node: self.last_node,
+            initial_state: state,
+            key: None,
+        }
+    }
+}
+
+impl Default for Blake2bParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone)]
+pub struct Blake2b {
     hashlen: usize,
-    message: Vec<u8>,
-    key: Option<&'a [u8]>,
     state: [u64; 8],
+    // only the most recent block is kept around: it cannot be compressed
+    // until we know whether it is the last one.
+    buffer: [u8; BLAKE2B_BLOCK_BYTES],
+    buflen: usize,
+    // t0 and t1 from the spec, the number of bytes compressed so far
+    counter: u128,
+    last_node: bool,
+    // for reset(): the state after the parameter block, and the key
+    initial_state: [u64; 8],
+    key: Option<[u8; 64]>,
 }
 
-impl<'a> Blake2b<'a> {
+impl Blake2b {
     /// Initialise a Blake2b hash function.
     /// hashlen is the length of the produced hash in bytes, up to 64.
     pub fn new(hashlen: usize) -> Self {
@@ -43,24 +256,29 @@ impl<'a> Blake2b<'a> {
             panic!("Blake2b hash length greater than supported! {hashlen} > 64 (supported)");
         }
 
-        Self {
-            hashlen,
-            message: vec![],
-            key: None,
-            state: BLAKE2B_IV,
-        }
+        Blake2bParams::new().hashlen(hashlen).build()
+    }
+
+    /// Initialise a keyed Blake2b hash function (a MAC).
+    /// The key can be up to 64 bytes long.
+    pub fn new_keyed(hashlen: usize, key: &[u8]) -> Self {
+        Blake2bParams::new().hashlen(hashlen).key(key).build()
     }
 
-    fn compress(&mut self, chunk: &[u8], compressed: usize, is_last_block: bool) {
+    fn compress(&mut self, chunk: &[u8; BLAKE2B_BLOCK_BYTES], is_last_block: bool) {
         let mut v = [0; 16];
         v[..8].copy_from_slice(&self.state);
         v[8..].copy_from_slice(&BLAKE2B_IV);
 
-        // TODO: how is it possible to address u128 addresses on a 64 bit system?
-        v[12] ^= compressed as u64;
-        // v[13] ^= High(compressed);
+        v[12] ^= self.counter as u64;
+        v[13] ^= (self.counter >> 64) as u64;
 
         v[14] ^= if is_last_block { !0 } else { 0 };
+        v[15] ^= if is_last_block && self.last_node {
+            !0
+        } else {
+            0
+        };
 
         let mut m = [0u64; 16];
         for i in 0..16 {
@@ -85,6 +303,22 @@ impl<'a> Blake2b<'a> {
         }
     }
 
+    /// Compresses the final block and returns the whole state, regardless
+    /// of the hash length in the parameter block.
+    pub(super) fn finalize(mut self) -> [u8; 64] {
+        // the last block is zero padded, but only the message bytes are counted
+        self.counter += self.buflen as u128;
+        self.buffer[self.buflen..].fill(0);
+        let block = self.buffer;
+        self.compress(&block, true);
+
+        let mut result = [0; 64];
+        for (bytes, word) in result.chunks_exact_mut(8).zip(self.state) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        result
+    }
+
     fn mix(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
         v[a] = v[a].overflowing_add(v[b]).0.overflowing_add(x).0;
         v[d] = (v[d] ^ v[a]).rotate_right(32);
@@ -97,57 +331,44 @@ impl<'a> Blake2b<'a> {
     }
 }
 
-impl HashFunction for Blake2b<'_> {
-    fn update(&mut self, message: &[u8]) {
-        self.message.extend_from_slice(message);
+/// The full 64 byte digest, like hash().
+impl Default for Blake2b {
+    fn default() -> Self {
+        Self::new(64)
     }
+}
 
-    fn digest(mut self) -> Vec<u8> {
-        let mut bytes_compressed = 0;
-        let mut bytes_remaining = self.message.len();
-
-        let keylen = match self.key {
-            Some(k) => k.len(),
-            None => 0,
-        };
-
-        // xor state[0] with 0x0101_kkhh -> k = keylen, h = hashlen
-        self.state[0] ^=
-            0x0101_0000 | (((keylen as u64) << 8) & 0xFF00) | (self.hashlen as u64 & 0xFF);
+impl HashFunction for Blake2b {
+    fn update(&mut self, mut message: &[u8]) {
+        let fill = BLAKE2B_BLOCK_BYTES - self.buflen;
 
-        // if there was a key: prepend the key to the 1st 128 bytes of the message
-        if let Some(key) = self.key {
-            let mut k = key.to_vec();
-            pad_trailing(&mut k, 0, 128);
-            prepend(&mut self.message, k);
-            bytes_remaining += 128;
-        }
+        // a full buffer is only compressed once there is more input after it
+        if message.len() > fill {
+            self.buffer[self.buflen..].copy_from_slice(&message[..fill]);
+            message = &message[fill..];
 
-        // iterate over the message in 128 byte chunks
-        // this use of take() feels like a code smell...?
-        let message = std::mem::take(&mut self.message);
-        let mut chunks = message.chunks(128).peekable();
+            let block = self.buffer;
+            self.counter += BLAKE2B_BLOCK_BYTES as u128;
+            self.compress(&block, false);
+            self.buflen = 0;
 
-        // if chunks is empty, it runs this and skips the for loop
-        if chunks.peek().is_none() {
-            let chunk = [0; 128];
-            self.compress(&chunk, 0, true);
+            // compress straight from the input, holding back the final block
+            while message.len() > BLAKE2B_BLOCK_BYTES {
+                let (block, rest) = message.split_at(BLAKE2B_BLOCK_BYTES);
+                self.counter += BLAKE2B_BLOCK_BYTES as u128;
+                self.compress(block.try_into().expect("len 128"), false);
+                message = rest;
+            }
         }
 
-        for chunk in chunks {
-            bytes_compressed += chunk.len();
-            bytes_remaining -= chunk.len();
-            let is_last_chunk = bytes_remaining == 0;
-            let mut c = chunk.to_vec();
-            pad_trailing(&mut c, 0, 128);
-            self.compress(&c, bytes_compressed, is_last_chunk);
-        }
+        self.buffer[self.buflen..self.buflen + message.len()].copy_from_slice(message);
+        self.buflen += message.len();
+    }
 
-        let mut result = Vec::new();
-        for word in self.state {
-            result.extend_from_slice(&word.to_le_bytes());
-        }
-        result.truncate(self.hashlen);
+    fn digest(self) -> Vec<u8> {
+        let hashlen = self.hashlen;
+        let mut result = self.finalize().to_vec();
+        result.truncate(hashlen);
         result
     }
 
@@ -158,11 +379,38 @@ impl HashFunction for Blake2b<'_> {
     }
 }
 
+impl FixedOutputHash for Blake2b {
+    const BLOCK_SIZE: usize = BLAKE2B_BLOCK_BYTES;
+    const OUTPUT_SIZE: usize = 64;
+
+    fn output_size(&self) -> usize {
+        self.hashlen
+    }
+
+    fn finalize_into(self, out: &mut [u8]) {
+        let hashlen = self.hashlen;
+        out.copy_from_slice(&self.finalize()[..hashlen]);
+    }
+
+    fn reset(&mut self) {
+        self.state = self.initial_state;
+        self.counter = 0;
+        self.buffer = [0; BLAKE2B_BLOCK_BYTES];
+        self.buflen = 0;
+
+        // the key is the first block of the message again
+        if let Some(key) = self.key {
+            self.buffer[..64].copy_from_slice(&key);
+            self.buflen = BLAKE2B_BLOCK_BYTES;
+        }
+    }
+}
+
 mod tests {
     // rust-analyzer does not detect the use of these? for some reason
     #![allow(unused_imports)]
     use crate::{
-        hash::{Blake2b, HashFunction},
+        hash::{Blake2b, Blake2bParams, FixedOutputHash, HashFunction},
         slice::u8_to_hexstr,
     };
 
@@ -171,5 +419,148 @@ mod tests {
         let res1 = u8_to_hexstr(&Blake2b::hash(b""));
         let exp1 = "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce";
         assert_eq!(res1, exp1);
+
+        let res2 = u8_to_hexstr(&Blake2b::hash(b"abc"));
+        let exp2 = "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923";
+        assert_eq!(res2, exp2);
+    }
+
+    #[test]
+    fn blake2b_multiple_blocks() {
+        let message: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+
+        let res1 = u8_to_hexstr(&Blake2b::hash(&message));
+        let exp1 = "c11e1c0340bd7e5a1b275f1230c962fad215ecb1391486e74e31b960a2f2996381a5fad092da06841d5f26e38f6ecfeaf441acbcd1c2de61aef121e7927175f5";
+        assert_eq!(res1, exp1);
+
+        // exactly two blocks, the second one must still be flagged as the last
+        let mut hasher = Blake2b::new(32);
+        hasher.update(&message[..256]);
+        let res2 = u8_to_hexstr(&hasher.digest());
+        let exp2 = "582f782226018ec33076bd8d1c42413530ac7e1126260ffc0f306ba3befc3f24";
+        assert_eq!(res2, exp2);
+    }
+
+    #[test]
+    fn blake2b_split_updates() {
+        let message: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+        let expected = Blake2b::hash(&message);
+
+        for split in [1, 7, 64, 127, 128, 129, 256, 999] {
+            let mut hasher = Blake2b::new(64);
+            for chunk in message.chunks(split) {
+                hasher.update(chunk);
+                hasher.update(b"");
+            }
+            assert_eq!(hasher.digest(), expected, "split into chunks of {split}");
+        }
+
+        for (a, b) in [(0, 128), (128, 256), (100, 300), (255, 257), (1, 999)] {
+            let mut hasher = Blake2b::new(64);
+            hasher.update(&message[..a]);
+            hasher.update(&message[a..b]);
+            hasher.update(&message[b..]);
+            assert_eq!(hasher.digest(), expected, "split at {a} and {b}");
+        }
+    }
+
+    #[test]
+    fn keyed_blake2b() {
+        // from blake2b-kat.txt, key = 00 01 02 .. 3f
+        let key: Vec<u8> = (0..64).collect();
+        let res1 = u8_to_hexstr(&Blake2b::new_keyed(64, &key).digest());
+        let exp1 = "10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568";
+        assert_eq!(res1, exp1);
+
+        let mut hasher = Blake2b::new_keyed(64, &key);
+        hasher.update(&(0..200).collect::<Vec<u8>>());
+        let res2 = u8_to_hexstr(&hasher.digest());
+        let exp2 = "3095a349d245708c7cf550118703d7302c27b60af5d4e67fc978f8a4e60953c7a04f92fcf41aee64321ccb707a895851552b1e37b00bc5e6b72fa5bcef9e3fff";
+        assert_eq!(res2, exp2);
+    }
+
+    #[test]
+    fn salted_personalized_blake2b() {
+        let mut hasher = Blake2bParams::new()
+            .hashlen(32)
+            .salt(b"sixteen byte sal")
+            .personal(b"my protocol v1\0\0")
+            .build();
+        hasher.update(b"abc");
+        let res1 = u8_to_hexstr(&hasher.digest());
+        let exp1 = "2aca779c0a42e09c8f8756c54b189f21a04e5fc03d933cdd0e67a2999c99af11";
+        assert_eq!(res1, exp1);
+
+        // shorter salts and personalizations are zero padded
+        let mut hasher = Blake2bParams::new()
+            .hashlen(32)
+            .salt(b"short")
+            .personal(b"app")
+            .build();
+        hasher.update(b"abc");
+        let res2 = u8_to_hexstr(&hasher.digest());
+        let exp2 = "b79acbcbf94355f4f1532740b0c39e1edda0ec7a4dca52544e1ef0009dcd8a1a";
+        assert_eq!(res2, exp2);
+    }
+
+    #[test]
+    fn tree_params_blake2b() {
+        let mut hasher = Blake2bParams::new()
+            .hashlen(48)
+            .key(b"secret")
+            .fanout(4)
+            .depth(3)
+            .leaf_length(4096)
+            .node_offset(0x0102030405060708)
+            .node_depth(2)
+            .inner_length(64)
+            .salt(b"salt")
+            .personal(b"person")
+            .build();
+        hasher.update(b"abc");
+        let res = u8_to_hexstr(&hasher.digest());
+        let exp = "67e3470815704fe9c66e3141b5e7c5db8aa25933cc57b8672065ee040b5c626243fb5c8fee4d07f2f45e960ec3a680a1";
+        assert_eq!(res, exp);
+    }
+
+    #[test]
+    fn blake2b_midstate_clone_and_reset() {
+        let mut prefix = Blake2b::new_keyed(64, b"secret key");
+        prefix.update(b"common prefix, then ");
+
+        let mut hasher = prefix.clone();
+        hasher.update(b"a");
+        let res1 = u8_to_hexstr(&hasher.digest());
+        let exp1 = "a97c9c1f74fdcb91f147a2ecebe7625b1dc8ad15d55a6e5686f7a4f235afd70e4e3269539313cda5054cedbd73e772e7a30647fabf0983cd83ca1121a3ddc1ce";
+        assert_eq!(res1, exp1);
+
+        let mut hasher = prefix.clone();
+        hasher.update(b"b");
+        let res2 = u8_to_hexstr(&hasher.digest());
+        let exp2 = "388fded6b07ac75a50c15bd54f9993b45cfb181f7968f4ccce01d16e5444116ca3fdc5566096bd06f5ba0f275f84e3a081c115d333e5f092ff3e18af19767397";
+        assert_eq!(res2, exp2);
+
+        // reset keeps the key
+        prefix.update(b"more data that gets thrown away");
+        prefix.reset();
+        prefix.update(b"common prefix, then a");
+        assert_eq!(u8_to_hexstr(&prefix.digest()), exp1);
+    }
+
+    #[test]
+    fn blake2b_finalize_array() {
+        let mut hasher = Blake2b::new(32);
+        hasher.update(b"abc");
+        assert_eq!(hasher.output_size(), 32);
+
+        let res1: [u8; 32] = hasher.finalize_array();
+        let exp1 = "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319";
+        assert_eq!(u8_to_hexstr(&res1), exp1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn blake2b_finalize_array_wrong_length() {
+        let _: [u8; 64] = Blake2b::new(32).finalize_array();
     }
 }
@@ -0,0 +1,481 @@
+use super::blake2b::SIGMA;
+use super::{FixedOutputHash, HashFunction};
+
+const BLAKE2S_IV: [u32; 8] = [
+    0x6a09e667, // Frac(sqrt(2))
+    0xbb67ae85, // Frac(sqrt(3))
+    0x3c6ef372, // Frac(sqrt(5))
+    0xa54ff53a, // Frac(sqrt(7))
+    0x510e527f, // Frac(sqrt(11))
+    0x9b05688c, // Frac(sqrt(13))
+    0x1f83d9ab, // Frac(sqrt(17))
+    0x5be0cd19, // Frac(sqrt(19))
+];
+
+const BLAKE2S_BLOCK_BYTES: usize = 64;
+
+/// The Blake2s parameter block (section 2.5 of the BLAKE2 spec).
+/// The defaults describe sequential hashing with a 32 byte digest.
+#[derive(Clone)]
+pub struct Blake2sParams {
+    hashlen: u8,
+    key: [u8; 32],
+    keylen: u8,
+    salt: [u8; 8],
+    personal: [u8; 8],
+    fanout: u8,
+    depth: u8,
+    leaf_length: u32,
+    node_offset: u64, // 48 bits
+    node_depth: u8,
+    inner_length: u8,
+    last_node: bool,
+}
+
+impl Blake2sParams {
+    pub fn new() -> Self {
+        Self {
+            hashlen: 32,
+            key: [0; 32],
+            keylen: 0,
+            salt: [0; 8],
+            personal: [0; 8],
+            fanout: 1,
+            depth: 1,
+            leaf_length: 0,
+            node_offset: 0,
+            node_depth: 0,
+            inner_length: 0,
+            last_node: false,
+        }
+    }
+
+    /// Length of the produced hash in bytes, from 8 up to 32. RFC 7693
+    /// allows shorter ones, but they are too weak to be useful.
+    pub fn hashlen(mut self, hashlen: usize) -> Self {
+        if !(8..=32).contains(&hashlen) {
+            panic!("Blake2s hash length not supported! {hashlen} (supported: 8 to 32)");
+        }
+        self.hashlen = hashlen as u8;
+        self
+    }
+
+    /// Key for MAC mode, up to 32 bytes. An empty key means unkeyed hashing.
+    pub fn key(mut self, key: &[u8]) -> Self {
+        if key.len() > 32 {
+            panic!(
+                "Blake2s key length greater than supported! {} > 32",
+                key.len()
+            );
+        }
+        self.key = [0; 32];
+        self.key[..key.len()].copy_from_slice(key);
+        self.keylen = key.len() as u8;
+        self
+    }
+
+    /// Salt of up to 8 bytes, zero padded if shorter.
+    pub fn salt(mut self, salt: &[u8]) -> Self {
+        if salt.len() > 8 {
+            panic!(
+                "Blake2s salt length greater than supported! {} > 8",
+                salt.len()
+            );
+        }
+        self.salt = [0; 8];
+        self.salt[..salt.len()].copy_from_slice(salt);
+        self
+    }
+
+    /// Personalization string of up to 8 bytes, zero padded if shorter.
+    pub fn personal(mut self, personal: &[u8]) -> Self {
+        if personal.len() > 8 {
+            panic!(
+                "Blake2s personalization length greater than supported! {} > 8",
+                personal.len()
+            );
+        }
+        self.personal = [0; 8];
+        self.personal[..personal.len()].copy_from_slice(personal);
+        self
+    }
+
+    /// Maximal number of children of a tree node, 0 for unlimited.
+    pub fn fanout(mut self, fanout: u8) -> Self {
+        self.fanout = fanout;
+        self
+    }
+
+    /// Maximal depth of the tree, from 1 to 255 (255 for unlimited).
+    pub fn depth(mut self, depth: u8) -> Self {
+        if depth == 0 {
+            panic!("Blake2s tree depth must be at least 1!");
+        }
+        self.depth = depth;
+        self
+    }
+
+    /// Maximal byte length of a leaf, 0 for unlimited.
+    pub fn leaf_length(mut self, leaf_length: u32) -> Self {
+        self.leaf_length = leaf_length;
+        self
+    }
+
+    /// Offset of the node within its level, starting at 0. Only 48 bits wide.
+    pub fn node_offset(mut self, node_offset: u64) -> Self {
+        if node_offset >= 1 << 48 {
+            panic!("Blake2s node offset does not fit in 48 bits! {node_offset:#x}");
+        }
+        self.node_offset = node_offset;
+        self
+    }
+
+    /// Depth of the node in the tree, 0 for leaves.
+    pub fn node_depth(mut self, node_depth: u8) -> Self {
+        self.node_depth = node_depth;
+        self
+    }
+
+    /// Length of the intermediate hashes fed to inner nodes, up to 32.
+    pub fn inner_length(mut self, inner_length: usize) -> Self {
+        if inner_length > 32 {
+            panic!("Blake2s inner length greater than supported! {inner_length} > 32");
+        }
+        self.inner_length = inner_length as u8;
+        self
+    }
+
+    /// Marks the node as the last (rightmost) one of its level.
+    pub fn last_node(mut self, last_node: bool) -> Self {
+        self.last_node = last_node;
+        self
+    }
+
+    /// The parameter block as the 8 words xored into the IV.
+    fn to_words(&self) -> [u32; 8] {
+        let le = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().expect("len 4"));
+
+        [
+            self.hashlen as u32
+                | (self.keylen as u32) << 8
+                | (self.fanout as u32) << 16
+                | (self.depth as u32) << 24,
+            self.leaf_length,
+            self.node_offset as u32,
+            (self.node_offset >> 32) as u32
+                | (self.node_depth as u32) << 16
+                | (self.inner_length as u32) << 24,
+            le(&self.salt[..4]),
+            le(&self.salt[4..]),
+            le(&self.personal[..4]),
+            le(&self.personal[4..]),
+        ]
+    }
+
+    /// Initialise a Blake2s hash function with these parameters.
+    pub fn build(&self) -> Blake2s {
+        let mut hasher = self.build_inner_node();
+
+        // if there was a key: it is padded to a full block and becomes the
+        // first block of the message
+        if self.keylen > 0 {
+            hasher.key = Some(self.key);
+            hasher.reset();
+        }
+
+        hasher
+    }
+
+    /// Nodes above the leaves of a tree carry the key length in their
+    /// parameter block, but never absorb the key itself.
+    pub(super) fn build_inner_node(&self) -> Blake2s {
+        let mut state = BLAKE2S_IV;
+        for (s, p) in state.iter_mut().zip(self.to_words()) {
+            *s ^= p;
+        }
+
+        Blake2s {
+            hashlen: self.hashlen as usize,
+            state,
+            buffer: [0; BLAKE2S_BLOCK_BYTES],
+            buflen: 0,
+            counter: 0,
+            last_node: self.last_node,
+            initial_state: state,
+            key: None,
+        }
+    }
+}
+
+impl Default for Blake2sParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Blake2s, the variant of BLAKE2 using 32 bit words.
+#[derive(Clone)]
+pub struct Blake2s {
+    hashlen: usize,
+    state: [u32; 8],
+    // same buffering as Blake2b: the last block has to be held back.
+    buffer: [u8; BLAKE2S_BLOCK_BYTES],
+    buflen: usize,
+    // t0 and t1 from the spec
+    counter: u64,
+    last_node: bool,
+    // for reset(): the state after the parameter block, and the key
+    initial_state: [u32; 8],
+    key: Option<[u8; 32]>,
+}
+
+impl Blake2s {
+    /// Initialise a Blake2s hash function.
+    /// hashlen is the length of the produced hash in bytes, from 8 to 32.
+    pub fn new(hashlen: usize) -> Self {
+        Blake2sParams::new().hashlen(hashlen).build()
+    }
+
+    /// Initialise a keyed Blake2s hash function (a MAC).
+    /// The key can be up to 32 bytes long.
+    pub fn new_keyed(hashlen: usize, key: &[u8]) -> Self {
+        Blake2sParams::new().hashlen(hashlen).key(key).build()
+    }
+
+    fn compress(&mut self, chunk: &[u8; BLAKE2S_BLOCK_BYTES], is_last_block: bool) {
+        let mut v = [0; 16];
+        v[..8].copy_from_slice(&self.state);
+        v[8..].copy_from_slice(&BLAKE2S_IV);
+
+        v[12] ^= self.counter as u32;
+        v[13] ^= (self.counter >> 32) as u32;
+
+        v[14] ^= if is_last_block { !0 } else { 0 };
+        v[15] ^= if is_last_block && self.last_node {
+            !0
+        } else {
+            0
+        };
+
+        let mut m = [0u32; 16];
+        for i in 0..16 {
+            m[i] = u32::from_le_bytes(chunk[i * 4..i * 4 + 4].try_into().expect("len 4"));
+        }
+
+        // Blake2s only uses the first 10 rows of the message schedule
+        for i in 0..10 {
+            Self::mix(&mut v, 0, 4, 8, 12, m[SIGMA[i][0]], m[SIGMA[i][1]]);
+            Self::mix(&mut v, 1, 5, 9, 13, m[SIGMA[i][2]], m[SIGMA[i][3]]);
+            Self::mix(&mut v, 2, 6, 10, 14, m[SIGMA[i][4]], m[SIGMA[i][5]]);
+            Self::mix(&mut v, 3, 7, 11, 15, m[SIGMA[i][6]], m[SIGMA[i][7]]);
+            Self::mix(&mut v, 0, 5, 10, 15, m[SIGMA[i][8]], m[SIGMA[i][9]]);
+            Self::mix(&mut v, 1, 6, 11, 12, m[SIGMA[i][10]], m[SIGMA[i][11]]);
+            Self::mix(&mut v, 2, 7, 8, 13, m[SIGMA[i][12]], m[SIGMA[i][13]]);
+            Self::mix(&mut v, 3, 4, 9, 14, m[SIGMA[i][14]], m[SIGMA[i][15]]);
+        }
+
+        for i in 0..8 {
+            self.state[i] ^= v[i] ^ v[i + 8];
+        }
+    }
+
+    /// Compresses the final block and returns the whole state, regardless
+    /// of the hash length in the parameter block.
+    pub(super) fn finalize(mut self) -> [u8; 32] {
+        self.counter += self.buflen as u64;
+        self.buffer[self.buflen..].fill(0);
+        let block = self.buffer;
+        self.compress(&block, true);
+
+        let mut result = [0; 32];
+        for (bytes, word) in result.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        result
+    }
+
+    fn mix(v: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, x: u32, y: u32) {
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+        v[d] = (v[d] ^ v[a]).rotate_right(16);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(12);
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+        v[d] = (v[d] ^ v[a]).rotate_right(8);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(7);
+    }
+}
+
+/// The full 32 byte digest, like hash().
+impl Default for Blake2s {
+    fn default() -> Self {
+        Self::new(32)
+    }
+}
+
+impl HashFunction for Blake2s {
+    fn update(&mut self, mut message: &[u8]) {
+        let fill = BLAKE2S_BLOCK_BYTES - self.buflen;
+
+        // a full buffer is only compressed once there is more input after it
+        if message.len() > fill {
+            self.buffer[self.buflen..].copy_from_slice(&message[..fill]);
+            message = &message[fill..];
+
+            let block = self.buffer;
+            self.counter += BLAKE2S_BLOCK_BYTES as u64;
+            self.compress(&block, false);
+            self.buflen = 0;
+
+            // compress straight from the input, holding back the final block
+            while message.len() > BLAKE2S_BLOCK_BYTES {
+                let (block, rest) = message.split_at(BLAKE2S_BLOCK_BYTES);
+                self.counter += BLAKE2S_BLOCK_BYTES as u64;
+                self.compress(block.try_into().expect("len 64"), false);
+                message = rest;
+            }
+        }
+
+        self.buffer[self.buflen..self.buflen + message.len()].copy_from_slice(message);
+        self.buflen += message.len();
+    }
+
+    fn digest(self) -> Vec<u8> {
+        let hashlen = self.hashlen;
+        let mut result = self.finalize().to_vec();
+        result.truncate(hashlen);
+        result
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new(32);
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Blake2s {
+    const BLOCK_SIZE: usize = BLAKE2S_BLOCK_BYTES;
+    const OUTPUT_SIZE: usize = 32;
+
+    fn output_size(&self) -> usize {
+        self.hashlen
+    }
+
+    fn finalize_into(self, out: &mut [u8]) {
+        let hashlen = self.hashlen;
+        out.copy_from_slice(&self.finalize()[..hashlen]);
+    }
+
+    fn reset(&mut self) {
+        self.state = self.initial_state;
+        self.counter = 0;
+        self.buffer = [0; BLAKE2S_BLOCK_BYTES];
+        self.buflen = 0;
+
+        // the key is the first block of the message again
+        if let Some(key) = self.key {
+            self.buffer[..32].copy_from_slice(&key);
+            self.buflen = BLAKE2S_BLOCK_BYTES;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::{Blake2s, Blake2sParams, HashFunction},
+        slice::u8_to_hexstr,
+    };
+
+    #[test]
+    fn basic_blake2s() {
+        let res1 = u8_to_hexstr(&Blake2s::hash(b""));
+        let exp1 = "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9";
+        assert_eq!(res1, exp1);
+
+        let res2 = u8_to_hexstr(&Blake2s::hash(b"abc"));
+        let exp2 = "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982";
+        assert_eq!(res2, exp2);
+    }
+
+    #[test]
+    fn blake2s_multiple_blocks() {
+        let message: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+
+        let res1 = u8_to_hexstr(&Blake2s::hash(&message));
+        let exp1 = "1c067a5e746fb0f6734efac9a8cdb0e11061f0077f255184365c690115392501";
+        assert_eq!(res1, exp1);
+
+        let mut hasher = Blake2s::new(16);
+        for chunk in message[..128].chunks(5) {
+            hasher.update(chunk);
+        }
+        let res2 = u8_to_hexstr(&hasher.digest());
+        let exp2 = "7a3b3fa888b60095e7b305efcf3c3dbc";
+        assert_eq!(res2, exp2);
+    }
+
+    #[test]
+    fn keyed_blake2s() {
+        // from blake2s-kat.txt, key = 00 01 02 .. 1f
+        let key: Vec<u8> = (0..32).collect();
+        let res1 = u8_to_hexstr(&Blake2s::new_keyed(32, &key).digest());
+        let exp1 = "48a8997da407876b3d79c0d92325ad3b89cbb754d86ab71aee047ad345fd2c49";
+        assert_eq!(res1, exp1);
+
+        let mut hasher = Blake2s::new_keyed(32, &key);
+        hasher.update(&(0..200).collect::<Vec<u8>>());
+        let res2 = u8_to_hexstr(&hasher.digest());
+        let exp2 = "13c88480a5d00d6c8c7ad2110d76a82d9b70f4fa6696d4e5dd42a066dcaf9920";
+        assert_eq!(res2, exp2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn blake2s_short_hash() {
+        Blake2s::new(7);
+    }
+
+    #[test]
+    fn salted_personalized_blake2s() {
+        let mut hasher = Blake2sParams::new()
+            .salt(b"saltsalt")
+            .personal(b"WireGrd!")
+            .build();
+        hasher.update(b"abc");
+        let res1 = u8_to_hexstr(&hasher.digest());
+        let exp1 = "bee5b7d33e2ddd39e930fb057e4f237940502b88f48426b25b306547a91ddf65";
+        assert_eq!(res1, exp1);
+
+        let mut hasher = Blake2sParams::new()
+            .hashlen(8)
+            .salt(b"s")
+            .personal(b"p")
+            .build();
+        hasher.update(b"abc");
+        let res2 = u8_to_hexstr(&hasher.digest());
+        let exp2 = "80ada158c845045d";
+        assert_eq!(res2, exp2);
+    }
+
+    #[test]
+    fn tree_params_blake2s() {
+        let mut hasher = Blake2sParams::new()
+            .hashlen(24)
+            .key(b"secret")
+            .fanout(8)
+            .depth(2)
+            .leaf_length(65536)
+            .node_offset(0x010203040506)
+            .node_depth(1)
+            .inner_length(32)
+            .salt(b"salt")
+            .personal(b"person")
+            .build();
+        hasher.update(b"abc");
+        let res = u8_to_hexstr(&hasher.digest());
+        let exp = "21a73f6f436055b04f3f1935fc15024877f8074b8498fd37";
+        assert_eq!(res, exp);
+    }
+}
@@ -0,0 +1,248 @@
+use super::{Blake2b, Blake2bParams, HashFunction, XofReader};
+
+/// The xof length that stands for "unknown in advance".
+const BLAKE2XB_UNKNOWN_LENGTH: u32 = u32::MAX;
+
+/// Blake2Xb, the extendable-output function built on Blake2b.
+///
+/// The message is hashed once into a 64 byte root hash H0, and the output
+/// stream is made of independent Blake2b hashes of H0, one per 64 bytes.
+pub struct Blake2xb {
+    root: Blake2b,
+    // salt and personalization are shared by the root and the output nodes
+    params: Blake2bParams,
+    xof_length: u32,
+}
+
+impl Blake2xb {
+    /// Initialise a Blake2Xb function producing xof_length bytes of output,
+    /// from 1 up to 2^32 - 2. The length is part of the hash, so streams of
+    /// different lengths are unrelated.
+    pub fn new(xof_length: u32) -> Self {
+        Self::with_params(Blake2bParams::new(), xof_length)
+    }
+
+    /// Initialise a Blake2Xb function whose output length is not known in
+    /// advance. The output stream can be read up to 256 GiB.
+    pub fn new_unbounded() -> Self {
+        Self::init(Blake2bParams::new(), BLAKE2XB_UNKNOWN_LENGTH)
+    }
+
+    /// Initialise a Blake2Xb function with the key, salt and personalization
+    /// of params. The other fields of the parameter block are fixed by
+    /// Blake2Xb, including the hash length. The output length is checked as
+    /// in new.
+    pub fn with_params(params: Blake2bParams, xof_length: u32) -> Self {
+        if xof_length == 0 {
+            panic!("Blake2Xb output length must be at least 1!");
+        }
+        // 2^32 - 1 stands for an unknown length, see new_unbounded
+        if xof_length == BLAKE2XB_UNKNOWN_LENGTH {
+            panic!("Blake2Xb output length greater than supported! {xof_length} > 2^32 - 2");
+        }
+
+        Self::init(params, xof_length)
+    }
+
+    fn init(params: Blake2bParams, xof_length: u32) -> Self {
+        let root = params
+            .clone()
+            .hashlen(64)
+            .fanout(1)
+            .depth(1)
+            .leaf_length(0)
+            .node_offset(Self::node_offset(0, xof_length))
+            .node_depth(0)
+            .inner_length(0)
+            .build();
+
+        let params = params
+            .key(&[])
+            .fanout(0)
+            .xof_output_depth()
+            .leaf_length(64)
+            .node_depth(0)
+            .inner_length(64);
+
+        Self {
+            root,
+            params,
+            xof_length,
+        }
+    }
+
+    /// Blake2Xb splits the node offset field in two: the lower half is the
+    /// actual node offset, the upper half is the xof length.
+    fn node_offset(node_offset: u32, xof_length: u32) -> u64 {
+        node_offset as u64 | (xof_length as u64) << 32
+    }
+
+    /// Hash the message into H0 and return a reader for the output stream.
+    pub fn finalize_xof(self) -> Blake2xbReader {
+        Blake2xbReader {
+            h0: self.root.finalize(),
+            params: self.params,
+            xof_length: self.xof_length,
+            position: 0,
+            block: [0; 64],
+        }
+    }
+}
+
+impl HashFunction for Blake2xb {
+    fn update(&mut self, message: &[u8]) {
+        self.root.update(message);
+    }
+
+    /// Reads the whole output stream, or 64 bytes if the length is unknown.
+    fn digest(self) -> Vec<u8> {
+        let len = match self.xof_length {
+            BLAKE2XB_UNKNOWN_LENGTH => 64,
+            len => len as usize,
+        };
+        let mut result = vec![0; len];
+        self.finalize_xof().read(&mut result);
+        result
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new(64);
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+/// The output stream of Blake2Xb.
+pub struct Blake2xbReader {
+    h0: [u8; 64],
+    params: Blake2bParams,
+    xof_length: u32,
+    // number of bytes read so far
+    position: u64,
+    // the output block that position points into
+    block: [u8; 64],
+}
+
+impl Blake2xbReader {
+    /// Computes output block i, which is shorter than 64 bytes only at the
+    /// end of a stream of known length.
+    fn output_block(&mut self, i: u32) {
+        let hashlen = match self.xof_length {
+            BLAKE2XB_UNKNOWN_LENGTH => 64,
+            len => (len as u64 - i as u64 * 64).min(64) as usize,
+        };
+
+        let mut node = self
+            .params
+            .clone()
+            .hashlen(hashlen)
+            .node_offset(Blake2xb::node_offset(i, self.xof_length))
+            .build();
+        node.update(&self.h0);
+        self.block[..hashlen].copy_from_slice(&node.finalize()[..hashlen]);
+    }
+}
+
+impl XofReader for Blake2xbReader {
+    fn read(&mut self, buf: &mut [u8]) {
+        let limit = match self.xof_length {
+            BLAKE2XB_UNKNOWN_LENGTH => 64 << 32,
+            len => len as u64,
+        };
+        if self.position + buf.len() as u64 > limit {
+            panic!("Blake2Xb output stream exhausted! only {limit} bytes available");
+        }
+
+        for byte in buf.iter_mut() {
+            let offset = (self.position % 64) as usize;
+            if offset == 0 {
+                self.output_block((self.position / 64) as u32);
+            }
+            *byte = self.block[offset];
+            self.position += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::{Blake2bParams, Blake2xb, HashFunction, XofReader},
+        slice::u8_to_hexstr,
+    };
+
+    #[test]
+    fn basic_blake2xb() {
+        let res1 = u8_to_hexstr(&Blake2xb::new(1).digest());
+        let exp1 = "34";
+        assert_eq!(res1, exp1);
+
+        let mut hasher = Blake2xb::new(100);
+        hasher.update(b"abc");
+        let res2 = u8_to_hexstr(&hasher.digest());
+        let exp2 = "e0f82b71c07860b65be612d2633becc46596a6c12a8772b561adec35721b7a5c44a7e075e8a3bc8c4fc8390a197be2085b4aa4385c207f24e46415defc659afd73bacb288080b10849aeea386c60cd3fa04c9bcbfeebaed6e98634d696b9d5bdef0ad2c5";
+        assert_eq!(res2, exp2);
+    }
+
+    #[test]
+    fn keyed_blake2xb() {
+        // the first two and the last keyed "blake2xb" entries of
+        // blake2-kat.json: key 00..3f, input 00..ff, output length 1 to 256
+        let key: Vec<u8> = (0..64).collect();
+        let message: Vec<u8> = (0..=255).collect();
+        let cases = [
+            (1, "64"),
+            (2, "f457"),
+            (
+                256,
+                "1e9b2c454e9de3a2d723d850331037dbf54133dbe27488ff757dd255833a27d8eb8a128ad12d0978b6884e25737086a704fb289aaaccf930d5b582ab4df1f55f0c429b6875edec3fe45464fa74164be056a55e243c4222c586bec5b18f39036aa903d98180f24f83d09a454dfa1e03a60e6a3ba4613e99c35f874d790174ee48a557f4f021ade4d1b278d7997ef094569b37b3db0505951e9ee8400adaea275c6db51b325ee730c69df97745b556ae41cd98741e28aa3a49544541eeb3da1b1e8fa4e8e9100d66dd0c7f5e2c271b1ecc077de79c462b9fe4c273543ecd82a5bea63c5acc01eca5fb780c7d7c8c9fe208ae8bd50cad1769693d92c6c8649d20d8",
+            ),
+        ];
+        for (length, exp) in cases {
+            let mut hasher = Blake2xb::with_params(Blake2bParams::new().key(&key), length);
+            hasher.update(&message);
+            assert_eq!(u8_to_hexstr(&hasher.digest()), exp);
+        }
+    }
+
+    #[test]
+    fn salted_personalized_blake2xb() {
+        let params = Blake2bParams::new().salt(b"salt").personal(b"person");
+        let mut hasher = Blake2xb::with_params(params, 32);
+        hasher.update(b"abc");
+        let res = u8_to_hexstr(&hasher.digest());
+        let exp = "db8b5d2880fa396442ed9a0b2219db94d6f45f7e786026d6a9e9e42f7204853e";
+        assert_eq!(res, exp);
+    }
+
+    #[test]
+    fn blake2xb_incremental_reads() {
+        let mut hasher = Blake2xb::new_unbounded();
+        hasher.update(b"abc");
+        let mut reader = hasher.finalize_xof();
+
+        let mut res = vec![0; 200];
+        let (a, rest) = res.split_at_mut(1);
+        let (b, c) = rest.split_at_mut(70);
+        reader.read(a);
+        reader.read(b);
+        reader.read(&mut c[..0]);
+        reader.read(c);
+
+        let exp = "ae080c1efbcf7f60ed52a04161d02b7ee63bed362534f0661da02c6e40cd208946d066b86b3dff620e57acea9cd72d3056cf6cb0c18341452a17ce2cced67b702669bf0bed358c1b708e97de2533b294cdd5e9e229678be36399b5b28d6541c4bc4e3079fb8a0fbdf6023a65f36c654947ce7c114a243670dad347f03275b5c5bd383e8d53fd0fe8f387ea3d6445fc6510c8a3b9fc5cced503b824504f0471bd3ac19514bdaf7a3c021dc44ca8ff6d656a6007d43b552f07560e8b79217060c1387971e8e3ee97d9";
+        assert_eq!(u8_to_hexstr(&res), exp);
+    }
+
+    #[test]
+    #[should_panic]
+    fn blake2xb_reserved_length() {
+        Blake2xb::new(u32::MAX);
+    }
+
+    #[test]
+    #[should_panic]
+    fn blake2xb_read_past_end() {
+        let mut reader = Blake2xb::new(10).finalize_xof();
+        reader.read(&mut [0; 11]);
+    }
+}
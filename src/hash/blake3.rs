@@ -0,0 +1,459 @@
+use super::{FixedOutputHash, HashFunction, XofReader};
+
+// same constants as the Blake2s (and SHA-256) IV
+const BLAKE3_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+// instead of a SIGMA table, the message words are permuted after every round
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+const BLAKE3_BLOCK_BYTES: usize = 64;
+const BLAKE3_CHUNK_BYTES: usize = 1024;
+const BLAKE3_OUT_BYTES: usize = 32;
+
+// domain separation flags
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+const KEYED_HASH: u32 = 1 << 4;
+const DERIVE_KEY_CONTEXT: u32 = 1 << 5;
+const DERIVE_KEY_MATERIAL: u32 = 1 << 6;
+
+fn mix(v: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, x: u32, y: u32) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(12);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(8);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(7);
+}
+
+/// The BLAKE3 compression function. Returns all 16 words of the state, the
+/// first 8 are the new chaining value and the rest are only used for output.
+fn compress(
+    chaining_value: &[u32; 8],
+    block: &[u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+) -> [u32; 16] {
+    let mut v = [0; 16];
+    v[..8].copy_from_slice(chaining_value);
+    v[8..12].copy_from_slice(&BLAKE3_IV[..4]);
+    v[12] = counter as u32;
+    v[13] = (counter >> 32) as u32;
+    v[14] = block_len;
+    v[15] = flags;
+
+    let mut m = *block;
+    for round in 0..7 {
+        mix(&mut v, 0, 4, 8, 12, m[0], m[1]);
+        mix(&mut v, 1, 5, 9, 13, m[2], m[3]);
+        mix(&mut v, 2, 6, 10, 14, m[4], m[5]);
+        mix(&mut v, 3, 7, 11, 15, m[6], m[7]);
+        mix(&mut v, 0, 5, 10, 15, m[8], m[9]);
+        mix(&mut v, 1, 6, 11, 12, m[10], m[11]);
+        mix(&mut v, 2, 7, 8, 13, m[12], m[13]);
+        mix(&mut v, 3, 4, 9, 14, m[14], m[15]);
+
+        if round < 6 {
+            m = std::array::from_fn(|i| m[MSG_PERMUTATION[i]]);
+        }
+    }
+
+    for i in 0..8 {
+        v[i] ^= v[i + 8];
+        v[i + 8] ^= chaining_value[i];
+    }
+    v
+}
+
+fn words_from_le_bytes<const N: usize>(bytes: &[u8]) -> [u32; N] {
+    std::array::from_fn(|i| u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().expect("len 4")))
+}
+
+fn first_8_words(words: [u32; 16]) -> [u32; 8] {
+    words[..8].try_into().expect("len 8")
+}
+
+/// The inputs of a compression that has not happened yet. Whether it is the
+/// root (and then how many output blocks it produces) is only known later.
+#[derive(Clone)]
+struct Output {
+    input_chaining_value: [u32; 8],
+    block: [u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+}
+
+impl Output {
+    fn chaining_value(&self) -> [u32; 8] {
+        first_8_words(compress(
+            &self.input_chaining_value,
+            &self.block,
+            self.counter,
+            self.block_len,
+            self.flags,
+        ))
+    }
+
+    /// Output block number `counter` of the root node.
+    fn root_output_block(&self, counter: u64) -> [u8; BLAKE3_BLOCK_BYTES] {
+        let words = compress(
+            &self.input_chaining_value,
+            &self.block,
+            counter,
+            self.block_len,
+            self.flags | ROOT,
+        );
+
+        let mut block = [0; BLAKE3_BLOCK_BYTES];
+        for (bytes, word) in block.chunks_exact_mut(4).zip(words) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        block
+    }
+}
+
+fn parent_output(left: [u32; 8], right: [u32; 8], key: &[u32; 8], flags: u32) -> Output {
+    let mut block = [0; 16];
+    block[..8].copy_from_slice(&left);
+    block[8..].copy_from_slice(&right);
+
+    Output {
+        input_chaining_value: *key,
+        block,
+        counter: 0,
+        block_len: BLAKE3_BLOCK_BYTES as u32,
+        flags: flags | PARENT,
+    }
+}
+
+/// A 1024 byte chunk being hashed, the leaves of the BLAKE3 tree.
+#[derive(Clone)]
+struct ChunkState {
+    chaining_value: [u32; 8],
+    chunk_counter: u64,
+    // like Blake2, the last block is held back until the chunk is complete
+    buffer: [u8; BLAKE3_BLOCK_BYTES],
+    buflen: usize,
+    blocks_compressed: u8,
+    flags: u32,
+}
+
+impl ChunkState {
+    fn new(key: &[u32; 8], chunk_counter: u64, flags: u32) -> Self {
+        Self {
+            chaining_value: *key,
+            chunk_counter,
+            buffer: [0; BLAKE3_BLOCK_BYTES],
+            buflen: 0,
+            blocks_compressed: 0,
+            flags,
+        }
+    }
+
+    fn len(&self) -> usize {
+        BLAKE3_BLOCK_BYTES * self.blocks_compressed as usize + self.buflen
+    }
+
+    fn start_flag(&self) -> u32 {
+        if self.blocks_compressed == 0 {
+            CHUNK_START
+        } else {
+            0
+        }
+    }
+
+    fn update(&mut self, mut message: &[u8]) {
+        while !message.is_empty() {
+            if self.buflen == BLAKE3_BLOCK_BYTES {
+                let block = words_from_le_bytes(&self.buffer);
+                self.chaining_value = first_8_words(compress(
+                    &self.chaining_value,
+                    &block,
+                    self.chunk_counter,
+                    BLAKE3_BLOCK_BYTES as u32,
+                    self.flags | self.start_flag(),
+                ));
+                self.blocks_compressed += 1;
+                self.buffer = [0; BLAKE3_BLOCK_BYTES];
+                self.buflen = 0;
+            }
+
+            let take = (BLAKE3_BLOCK_BYTES - self.buflen).min(message.len());
+            self.buffer[self.buflen..self.buflen + take].copy_from_slice(&message[..take]);
+            self.buflen += take;
+            message = &message[take..];
+        }
+    }
+
+    fn output(&self) -> Output {
+        Output {
+            input_chaining_value: self.chaining_value,
+            block: words_from_le_bytes(&self.buffer),
+            counter: self.chunk_counter,
+            block_len: self.buflen as u32,
+            flags: self.flags | self.start_flag() | CHUNK_END,
+        }
+    }
+}
+
+/// BLAKE3, a Merkle tree of 1024 byte chunks hashed with a reduced round
+/// version of the Blake2s compression function.
+#[derive(Clone)]
+pub struct Blake3 {
+    chunk_state: ChunkState,
+    key: [u32; 8],
+    // chaining values of completed subtrees, at most one per tree level
+    cv_stack: Vec<[u32; 8]>,
+    flags: u32,
+}
+
+impl Blake3 {
+    /// Initialise a BLAKE3 hash function in regular hashing mode.
+    pub fn new() -> Self {
+        Self::with_key_and_flags(BLAKE3_IV, 0)
+    }
+
+    /// Initialise BLAKE3 in keyed hashing mode (a MAC).
+    pub fn new_keyed(key: &[u8; 32]) -> Self {
+        Self::with_key_and_flags(words_from_le_bytes(key), KEYED_HASH)
+    }
+
+    /// Initialise BLAKE3 in key derivation mode. The context string should
+    /// be hardcoded, globally unique and application specific, the key
+    /// material is then fed through update().
+    pub fn new_derive_key(context: &str) -> Self {
+        let mut context_hasher = Self::with_key_and_flags(BLAKE3_IV, DERIVE_KEY_CONTEXT);
+        context_hasher.update(context.as_bytes());
+        let context_key = context_hasher.root_output().root_output_block(0);
+
+        Self::with_key_and_flags(words_from_le_bytes(&context_key), DERIVE_KEY_MATERIAL)
+    }
+
+    /// Derive a 32 byte key from key_material, see new_derive_key().
+    pub fn derive_key(context: &str, key_material: &[u8]) -> [u8; 32] {
+        let mut hasher = Self::new_derive_key(context);
+        hasher.update(key_material);
+        hasher.digest().try_into().expect("len 32")
+    }
+
+    fn with_key_and_flags(key: [u32; 8], flags: u32) -> Self {
+        Self {
+            chunk_state: ChunkState::new(&key, 0, flags),
+            key,
+            cv_stack: Vec::new(),
+            flags,
+        }
+    }
+
+    /// Adds the chaining value of a completed chunk. The number of completed
+    /// chunks tells us which subtrees are complete: every trailing zero bit
+    /// of total_chunks merges the two topmost stack entries.
+    fn add_chunk_chaining_value(&mut self, mut new_cv: [u32; 8], mut total_chunks: u64) {
+        while total_chunks & 1 == 0 {
+            let left = self.cv_stack.pop().expect("completed subtree on the stack");
+            new_cv = parent_output(left, new_cv, &self.key, self.flags).chaining_value();
+            total_chunks >>= 1;
+        }
+        self.cv_stack.push(new_cv);
+    }
+
+    /// Merges the rest of the stack from right to left, without knowing yet
+    /// how many output bytes the root node has to produce.
+    fn root_output(&self) -> Output {
+        let mut output = self.chunk_state.output();
+        for left in self.cv_stack.iter().rev() {
+            output = parent_output(*left, output.chaining_value(), &self.key, self.flags);
+        }
+        output
+    }
+
+    /// Return a reader for the unbounded output stream.
+    pub fn finalize_xof(&self) -> Blake3Reader {
+        Blake3Reader {
+            output: self.root_output(),
+            position: 0,
+            block: [0; BLAKE3_BLOCK_BYTES],
+        }
+    }
+}
+
+impl Default for Blake3 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Blake3 {
+    fn update(&mut self, mut message: &[u8]) {
+        while !message.is_empty() {
+            // only finish a chunk once there is more input, the last chunk
+            // may have to become the root node
+            if self.chunk_state.len() == BLAKE3_CHUNK_BYTES {
+                let chunk_cv = self.chunk_state.output().chaining_value();
+                let total_chunks = self.chunk_state.chunk_counter + 1;
+                self.add_chunk_chaining_value(chunk_cv, total_chunks);
+                self.chunk_state = ChunkState::new(&self.key, total_chunks, self.flags);
+            }
+
+            let take = (BLAKE3_CHUNK_BYTES - self.chunk_state.len()).min(message.len());
+            self.chunk_state.update(&message[..take]);
+            message = &message[take..];
+        }
+    }
+
+    fn digest(self) -> Vec<u8> {
+        self.root_output().root_output_block(0)[..BLAKE3_OUT_BYTES].to_vec()
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Blake3 {
+    const BLOCK_SIZE: usize = BLAKE3_BLOCK_BYTES;
+    const OUTPUT_SIZE: usize = BLAKE3_OUT_BYTES;
+
+    fn finalize_into(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.root_output().root_output_block(0)[..BLAKE3_OUT_BYTES]);
+    }
+
+    fn reset(&mut self) {
+        self.chunk_state = ChunkState::new(&self.key, 0, self.flags);
+        self.cv_stack.clear();
+    }
+}
+
+/// The output stream of BLAKE3, which can be read up to 2^64 bytes.
+pub struct Blake3Reader {
+    output: Output,
+    // number of bytes read so far
+    position: u64,
+    // the output block that position points into
+    block: [u8; BLAKE3_BLOCK_BYTES],
+}
+
+impl XofReader for Blake3Reader {
+    fn read(&mut self, buf: &mut [u8]) {
+        for byte in buf.iter_mut() {
+            let offset = (self.position % BLAKE3_BLOCK_BYTES as u64) as usize;
+            if offset == 0 {
+                self.block = self
+                    .output
+                    .root_output_block(self.position / BLAKE3_BLOCK_BYTES as u64);
+            }
+            *byte = self.block[offset];
+            self.position += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::{Blake3, HashFunction, XofReader},
+        slice::{decode_hex, u8_to_hexstr},
+    };
+
+    const TEST_VECTORS: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/blake3_test_vectors.json"
+    ));
+
+    /// The value of a `"name": value` line in the test vector file.
+    fn json_field<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+        let value = line.trim().strip_prefix(&format!("\"{name}\": "))?;
+        Some(value.trim_end_matches(',').trim_matches('"'))
+    }
+
+    /// Checks the whole extended output, and that the default length digest
+    /// is a prefix of it.
+    fn check(hasher: Blake3, expected: &str, input_len: usize) {
+        let mut extended = vec![0; expected.len() / 2];
+        hasher.finalize_xof().read(&mut extended);
+        assert_eq!(u8_to_hexstr(&extended), expected, "input_len {input_len}");
+        assert_eq!(hasher.digest(), extended[..32], "input_len {input_len}");
+    }
+
+    #[test]
+    fn blake3_test_vectors() {
+        let key: [u8; 32] = TEST_VECTORS
+            .lines()
+            .find_map(|line| json_field(line, "key"))
+            .expect("key")
+            .as_bytes()
+            .try_into()
+            .expect("32 byte key");
+        let context = TEST_VECTORS
+            .lines()
+            .find_map(|line| json_field(line, "context_string"))
+            .expect("context string");
+
+        let mut input = Vec::new();
+        let mut cases = 0;
+        for line in TEST_VECTORS.lines() {
+            if let Some(len) = json_field(line, "input_len") {
+                input = (0..len.parse().expect("length"))
+                    .map(|i: usize| (i % 251) as u8)
+                    .collect();
+                cases += 1;
+            } else if let Some(expected) = json_field(line, "hash") {
+                let mut hasher = Blake3::new();
+                hasher.update(&input);
+                check(hasher, expected, input.len());
+            } else if let Some(expected) = json_field(line, "keyed_hash") {
+                let mut hasher = Blake3::new_keyed(&key);
+                hasher.update(&input);
+                check(hasher, expected, input.len());
+            } else if let Some(expected) = json_field(line, "derive_key") {
+                let mut hasher = Blake3::new_derive_key(context);
+                hasher.update(&input);
+                check(hasher, expected, input.len());
+
+                let derived = Blake3::derive_key(context, &input);
+                assert_eq!(derived[..], decode_hex(&expected[..64]));
+            }
+        }
+        assert!(cases > 0, "no test vectors found");
+    }
+
+    #[test]
+    fn blake3_split_updates() {
+        let message: Vec<u8> = (0..5000).map(|i| (i % 251) as u8).collect();
+        let expected = Blake3::hash(&message);
+
+        for split in [1, 63, 64, 65, 1023, 1024, 1025, 4096] {
+            let mut hasher = Blake3::new();
+            for chunk in message.chunks(split) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.digest(), expected, "split into chunks of {split}");
+        }
+    }
+
+    #[test]
+    fn blake3_incremental_reads() {
+        let mut hasher = Blake3::new();
+        hasher.update(b"abc");
+
+        let mut expected = vec![0; 300];
+        hasher.finalize_xof().read(&mut expected);
+
+        let mut reader = hasher.finalize_xof();
+        let mut res = vec![0; 300];
+        for chunk in res.chunks_mut(37) {
+            reader.read(chunk);
+        }
+        assert_eq!(res, expected);
+    }
+}
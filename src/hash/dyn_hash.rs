@@ -0,0 +1,170 @@
+use super::{DigestError, FixedOutputHash};
+
+/// The object safe part of FixedOutputHash, implemented for every hash.
+trait DynHashInner {
+    fn update(&mut self, message: &[u8]);
+    fn output_size(&self) -> usize;
+    fn block_size(&self) -> usize;
+    fn finalize_into(self: Box<Self>, out: &mut [u8]);
+    fn try_finalize_into(self: Box<Self>, out: &mut [u8]) -> Result<(), DigestError>;
+    fn reset(&mut self);
+    fn box_clone(&self) -> Box<dyn DynHashInner>;
+}
+
+impl<H: FixedOutputHash + 'static> DynHashInner for H {
+    fn update(&mut self, message: &[u8]) {
+        H::update(self, message);
+    }
+
+    fn output_size(&self) -> usize {
+        H::output_size(self)
+    }
+
+    fn block_size(&self) -> usize {
+        H::BLOCK_SIZE
+    }
+
+    fn finalize_into(self: Box<Self>, out: &mut [u8]) {
+        H::finalize_into(*self, out);
+    }
+
+    fn try_finalize_into(self: Box<Self>, out: &mut [u8]) -> Result<(), DigestError> {
+        H::try_finalize_into(*self, out)
+    }
+
+    fn reset(&mut self) {
+        H::reset(self);
+    }
+
+    fn box_clone(&self) -> Box<dyn DynHashInner> {
+        Box::new(self.clone())
+    }
+}
+
+/// A hash function picked at runtime, e.g. from a config file or a
+/// signature algorithm identifier.
+pub struct DynHash {
+    inner: Box<dyn DynHashInner>,
+}
+
+impl DynHash {
+    pub fn new<H: FixedOutputHash + 'static>(hasher: H) -> Self {
+        Self {
+            inner: Box::new(hasher),
+        }
+    }
+
+    pub fn update(&mut self, message: &[u8]) {
+        self.inner.update(message);
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.inner.output_size()
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.inner.block_size()
+    }
+
+    /// write the digest into out, which has to be output_size() bytes long
+    pub fn finalize_into(self, out: &mut [u8]) {
+        self.inner.finalize_into(out);
+    }
+
+    pub fn digest(self) -> Vec<u8> {
+        let mut result = vec![0; self.output_size()];
+        self.finalize_into(&mut result);
+        result
+    }
+
+    /// like finalize_into(), but with an error for input the hash refuses,
+    /// see DigestError
+    pub fn try_finalize_into(self, out: &mut [u8]) -> Result<(), DigestError> {
+        self.inner.try_finalize_into(out)
+    }
+
+    pub fn try_digest(self) -> Result<Vec<u8>, DigestError> {
+        let mut result = vec![0; self.output_size()];
+        self.try_finalize_into(&mut result)?;
+        Ok(result)
+    }
+
+    /// go back to the initial state, keeping the settings (and key)
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+impl Clone for DynHash {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.box_clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::{Blake2b, DynHash, Sha3_256, Sha256},
+        slice::u8_to_hexstr,
+    };
+
+    fn pick(name: &str) -> DynHash {
+        match name {
+            "blake2b-256" => DynHash::new(Blake2b::new(32)),
+            "sha256" => DynHash::new(Sha256::new()),
+            "sha3-256" => DynHash::new(Sha3_256::new()),
+            _ => panic!("unknown hash {name}"),
+        }
+    }
+
+    #[test]
+    fn dyn_hash_by_name() {
+        let cases = [
+            (
+                "blake2b-256",
+                128,
+                "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319",
+            ),
+            (
+                "sha256",
+                64,
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                "sha3-256",
+                136,
+                "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            ),
+        ];
+
+        for (name, block_size, expected) in cases {
+            let mut hasher = pick(name);
+            assert_eq!(hasher.block_size(), block_size);
+            assert_eq!(hasher.output_size(), 32);
+
+            hasher.update(b"abc");
+            assert_eq!(u8_to_hexstr(&hasher.digest()), expected);
+        }
+    }
+
+    #[test]
+    fn dyn_hash_clone_and_reset() {
+        let mut hasher = pick("sha256");
+        hasher.update(b"ab");
+        let mut fork = hasher.clone();
+        fork.update(b"d");
+
+        hasher.reset();
+        hasher.update(b"abc");
+        let mut res1 = [0; 32];
+        hasher.finalize_into(&mut res1);
+        let exp1 = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(u8_to_hexstr(&res1), exp1);
+
+        let res2 = u8_to_hexstr(&fork.digest());
+        let exp2 = "a52d159f262b2c6ddb724a61840befc36eb30c88877a4030b65cbe86298449c9";
+        assert_eq!(res2, exp2);
+    }
+}
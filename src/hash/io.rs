@@ -0,0 +1,132 @@
+use std::{
+    fs::File,
+    io::{self, ErrorKind, Read, Write},
+    path::Path,
+};
+
+use super::HashFunction;
+
+// the read buffer of hash_reader
+const BUFFER_BYTES: usize = 64 * 1024;
+
+/// Feeds everything written to it into a hash function, so io::copy and
+/// friends can hash a stream.
+pub struct HashWriter<H: HashFunction> {
+    hasher: H,
+}
+
+impl<H: HashFunction> HashWriter<H> {
+    pub fn new(hasher: H) -> Self {
+        Self { hasher }
+    }
+
+    /// Returns the hasher, to call digest() or try_digest() on.
+    pub fn into_inner(self) -> H {
+        self.hasher
+    }
+}
+
+impl<H: HashFunction> Write for HashWriter<H> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.hasher.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Hashes everything the reader returns until the end of the stream. If the
+/// hash refuses the input (a SHA-1 collision attack), the error is of kind
+/// InvalidData and wraps the DigestError.
+pub fn hash_reader<H: HashFunction, R: Read>(mut hasher: H, mut reader: R) -> io::Result<Vec<u8>> {
+    let mut buffer = vec![0; BUFFER_BYTES];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => {
+                return hasher
+                    .try_digest()
+                    .map_err(|e| io::Error::new(ErrorKind::InvalidData, e));
+            }
+            Ok(n) => hasher.update(&buffer[..n]),
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Hashes the contents of a file, e.g. hash_file(Blake2b::new(64), path) is
+/// what b2sum prints.
+pub fn hash_file<H: HashFunction, P: AsRef<Path>>(hasher: H, path: P) -> io::Result<Vec<u8>> {
+    hash_reader(hasher, File::open(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor, ErrorKind, Read};
+
+    use crate::{
+        hash::{Blake2b, HashFunction, HashWriter, Sha256, hash_file, hash_reader},
+        slice::u8_to_hexstr,
+    };
+
+    /// Longer than the read buffer, so it takes more than one read.
+    fn message() -> Vec<u8> {
+        (0..100_000).map(|i| (i % 251) as u8).collect()
+    }
+
+    /// Returns an Interrupted error before every chunk of the inner reader.
+    struct Interrupting<R> {
+        inner: R,
+        interrupt: bool,
+    }
+
+    impl<R: Read> Read for Interrupting<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt {
+                return Err(ErrorKind::Interrupted.into());
+            }
+            let len = buf.len().min(1000);
+            self.inner.read(&mut buf[..len])
+        }
+    }
+
+    #[test]
+    fn hash_writer_with_io_copy() {
+        let mut writer = HashWriter::new(Sha256::new());
+        io::copy(&mut Cursor::new(message()), &mut writer).unwrap();
+
+        let res1 = writer.into_inner().digest();
+        assert_eq!(res1, Sha256::hash(&message()));
+    }
+
+    #[test]
+    fn basic_hash_reader() {
+        let res1 = u8_to_hexstr(&hash_reader(Blake2b::new(64), Cursor::new(message())).unwrap());
+        let exp1 = "a0b4e1c912a57364ed019d57296b9f14fd4ac7a36dc5b324706ba2b1497cf7b58b813ef6d86efab3a9461768ea9a552ab97554f8c4b010ed26d04ae6158bcedf";
+        assert_eq!(res1, exp1);
+
+        let reader = Interrupting {
+            inner: Cursor::new(message()),
+            interrupt: false,
+        };
+        let res2 = u8_to_hexstr(&hash_reader(Blake2b::new(64), reader).unwrap());
+        assert_eq!(res2, exp1);
+    }
+
+    #[test]
+    fn basic_hash_file() {
+        let path = std::env::temp_dir().join(format!("badcrypto-hash-file-{}", std::process::id()));
+        std::fs::write(&path, message()).unwrap();
+        let res1 = hash_file(Blake2b::new(64), &path);
+        std::fs::remove_file(&path).unwrap();
+
+        let res1 = res1.unwrap();
+        assert_eq!(res1, Blake2b::hash(&message()));
+
+        let missing = hash_file(Blake2b::new(64), &path);
+        assert_eq!(missing.unwrap_err().kind(), ErrorKind::NotFound);
+    }
+}
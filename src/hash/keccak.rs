@@ -0,0 +1,174 @@
+// iota step constants, from the LFSR in FIPS 202 section 3.2.5
+const KECCAK_RC: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+// rho step rotation offsets, indexed by x + 5 * y
+const KECCAK_RHO: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+const KECCAK_STATE_BYTES: usize = 200;
+
+/// The Keccak-f[1600] permutation. Lane (x, y) of the state is a[x + 5 * y].
+pub fn keccak_f1600(a: &mut [u64; 25]) {
+    for rc in KECCAK_RC {
+        // theta: xor every lane with the parities of two neighbouring columns
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[x + 5 * y] ^= d;
+            }
+        }
+
+        // rho and pi: rotate every lane, then move (x, y) to (y, 2x + 3y)
+        let mut b = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = a[x + 5 * y].rotate_left(KECCAK_RHO[x + 5 * y]);
+            }
+        }
+
+        // chi: the only non-linear step
+        for y in 0..5 {
+            for x in 0..5 {
+                a[x + 5 * y] = b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+
+        // iota
+        a[0] ^= rc;
+    }
+}
+
+/// A sponge over Keccak-f[1600]. The rate is the number of state bytes
+/// message blocks are xored into, the rest of the state (the capacity) is
+/// never touched directly.
+#[derive(Clone)]
+pub struct KeccakSponge {
+    state: [u64; 25],
+    rate: usize,
+    // byte offset into the rate, for absorbing and then squeezing
+    position: usize,
+    squeezing: bool,
+}
+
+impl KeccakSponge {
+    /// rate is in bytes, and has to be a multiple of 8 below 200.
+    pub fn new(rate: usize) -> Self {
+        if rate == 0 || rate >= KECCAK_STATE_BYTES || !rate.is_multiple_of(8) {
+            panic!("Keccak sponge rate not supported! {rate} bytes");
+        }
+
+        Self {
+            state: [0; 25],
+            rate,
+            position: 0,
+            squeezing: false,
+        }
+    }
+
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    fn xor_byte(&mut self, i: usize, byte: u8) {
+        self.state[i / 8] ^= (byte as u64) << (8 * (i % 8));
+    }
+
+    fn byte(&self, i: usize) -> u8 {
+        (self.state[i / 8] >> (8 * (i % 8))) as u8
+    }
+
+    pub fn absorb(&mut self, message: &[u8]) {
+        if self.squeezing {
+            panic!("Keccak sponge cannot absorb after squeezing!");
+        }
+
+        for &byte in message {
+            self.xor_byte(self.position, byte);
+            self.position += 1;
+            if self.position == self.rate {
+                keccak_f1600(&mut self.state);
+                self.position = 0;
+            }
+        }
+    }
+
+    /// Pads the last block and switches to squeezing. The domain separation
+    /// bits (0x06 for SHA-3, 0x1f for SHAKE, 0x01 for the original Keccak)
+    /// come first, and already include the first bit of the pad10*1 padding.
+    pub fn finalize(&mut self, domain: u8) {
+        if self.squeezing {
+            return;
+        }
+
+        self.xor_byte(self.position, domain);
+        self.xor_byte(self.rate - 1, 0x80);
+        keccak_f1600(&mut self.state);
+        self.position = 0;
+        self.squeezing = true;
+    }
+
+    /// Reads output from the sponge, finalize() has to be called first.
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        if !self.squeezing {
+            panic!("Keccak sponge has to be finalized before squeezing!");
+        }
+
+        for byte in out.iter_mut() {
+            if self.position == self.rate {
+                keccak_f1600(&mut self.state);
+                self.position = 0;
+            }
+            *byte = self.byte(self.position);
+            self.position += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::keccak_f1600;
+
+    #[test]
+    fn keccak_f1600_zero_state() {
+        // KeccakF-1600-IntermediateValues.txt, the permutation of zero
+        let mut state = [0u64; 25];
+        keccak_f1600(&mut state);
+        assert_eq!(state[0], 0xf1258f7940e1dde7);
+        assert_eq!(state[1], 0x84d5ccf933c0478a);
+        assert_eq!(state[24], 0xeaf1ff7b5ceca249);
+
+        keccak_f1600(&mut state);
+        assert_eq!(state[0], 0x2d5c954df96ecb3c);
+        assert_eq!(state[24], 0x20d06cd26a8fbf5c);
+    }
+}
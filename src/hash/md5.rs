@@ -0,0 +1,190 @@
+use super::{FixedOutputHash, HashFunction};
+
+// floor(abs(sin(i + 1)) * 2^32)
+const MD5_K: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+// rotation amounts, four per round
+const MD5_S: [[u32; 4]; 4] = [
+    [7, 12, 17, 22],
+    [5, 9, 14, 20],
+    [4, 11, 16, 23],
+    [6, 10, 15, 21],
+];
+
+const MD5_IV: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+const MD5_BLOCK_BYTES: usize = 64;
+
+/// MD5 (RFC 1321). Collisions take seconds to find, only use it to check
+/// data that was hashed with it before.
+#[derive(Clone)]
+pub struct Md5 {
+    state: [u32; 4],
+    buffer: [u8; MD5_BLOCK_BYTES],
+    buflen: usize,
+    // message length in bytes
+    length: u64,
+}
+
+impl Md5 {
+    pub fn new() -> Self {
+        Self {
+            state: MD5_IV,
+            buffer: [0; MD5_BLOCK_BYTES],
+            buflen: 0,
+            length: 0,
+        }
+    }
+
+    fn finish(mut self) -> [u8; 16] {
+        // the same padding as SHA-1, but with the length in little endian
+        let bit_length = self.length.wrapping_mul(8);
+        let mut padding = [0u8; MD5_BLOCK_BYTES];
+        padding[0] = 0x80;
+        let zeros = (MD5_BLOCK_BYTES * 2 - 8 - 1 - self.buflen) % MD5_BLOCK_BYTES;
+        self.update(&padding[..1 + zeros]);
+        self.update(&bit_length.to_le_bytes());
+        debug_assert_eq!(self.buflen, 0);
+
+        let mut result = [0; 16];
+        for (bytes, word) in result.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        result
+    }
+
+    fn compress(&mut self, block: &[u8; MD5_BLOCK_BYTES]) {
+        let mut m = [0u32; 16];
+        for i in 0..16 {
+            m[i] = u32::from_le_bytes(block[i * 4..i * 4 + 4].try_into().expect("len 4"));
+        }
+
+        let [mut a, mut b, mut c, mut d] = self.state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let t = a
+                .wrapping_add(f)
+                .wrapping_add(MD5_K[i])
+                .wrapping_add(m[g])
+                .rotate_left(MD5_S[i / 16][i % 4]);
+
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(t);
+        }
+
+        for (s, v) in self.state.iter_mut().zip([a, b, c, d]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+}
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Md5 {
+    fn update(&mut self, mut message: &[u8]) {
+        self.length += message.len() as u64;
+
+        while !message.is_empty() {
+            let take = (MD5_BLOCK_BYTES - self.buflen).min(message.len());
+            self.buffer[self.buflen..self.buflen + take].copy_from_slice(&message[..take]);
+            self.buflen += take;
+            message = &message[take..];
+
+            if self.buflen == MD5_BLOCK_BYTES {
+                let block = self.buffer;
+                self.compress(&block);
+                self.buflen = 0;
+            }
+        }
+    }
+
+    fn digest(self) -> Vec<u8> {
+        self.finish().to_vec()
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Md5 {
+    const BLOCK_SIZE: usize = MD5_BLOCK_BYTES;
+    const OUTPUT_SIZE: usize = 16;
+
+    fn finalize_into(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.finish());
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::{HashFunction, Md5},
+        slice::u8_to_hexstr,
+    };
+
+    #[test]
+    fn basic_md5() {
+        // the RFC 1321 test suite
+        let cases: [(&[u8], &str); 7] = [
+            (b"", "d41d8cd98f00b204e9800998ecf8427e"),
+            (b"a", "0cc175b9c0f1b6a831c399e269772661"),
+            (b"abc", "900150983cd24fb0d6963f7d28e17f72"),
+            (b"message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            (
+                b"abcdefghijklmnopqrstuvwxyz",
+                "c3fcd3d76192e4007dfb496cca67e13b",
+            ),
+            (
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "d174ab98d277d9f5a5611c2c9f419d9f",
+            ),
+            (
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "57edf4a22be3c955ac49da2e2107b67a",
+            ),
+        ];
+
+        for (message, expected) in cases {
+            assert_eq!(u8_to_hexstr(&Md5::hash(message)), expected);
+        }
+    }
+
+    #[test]
+    fn md5_streaming() {
+        let message: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+        let mut hasher = Md5::new();
+        for chunk in message.chunks(7) {
+            hasher.update(chunk);
+        }
+        let res1 = u8_to_hexstr(&hasher.digest());
+        let exp1 = "a24f1e3ef66950e1327f210e3997ba2c";
+        assert_eq!(res1, exp1);
+    }
+}
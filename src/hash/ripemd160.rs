@@ -0,0 +1,250 @@
+use super::{FixedOutputHash, HashFunction, Sha256};
+
+// message word order of the left and right lines
+const RIPEMD160_R: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5,
+    2, 14, 11, 8, 3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, 1, 9, 11, 10, 0, 8, 12, 4,
+    13, 3, 7, 15, 14, 5, 6, 2, 4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+const RIPEMD160_R_PRIME: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, 6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12,
+    4, 9, 1, 2, 15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, 8, 6, 4, 1, 3, 11, 15, 0, 5,
+    12, 2, 13, 9, 7, 10, 14, 12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+// rotation amounts of the left and right lines
+const RIPEMD160_S: [u32; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, 7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15,
+    9, 11, 7, 13, 12, 11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, 11, 12, 14, 15, 14,
+    15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, 9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+const RIPEMD160_S_PRIME: [u32; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, 9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12,
+    7, 6, 15, 13, 11, 9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, 15, 5, 8, 11, 14, 14,
+    6, 14, 6, 9, 12, 9, 12, 5, 15, 8, 8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+// sqrt(2), sqrt(3), sqrt(5) and sqrt(7) times 2^30 for the left line, and
+// cbrt of the same for the right line
+const RIPEMD160_K: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+const RIPEMD160_K_PRIME: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+const RIPEMD160_IV: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+const RIPEMD160_BLOCK_BYTES: usize = 64;
+
+/// RIPEMD-160, as used in Bitcoin addresses.
+#[derive(Clone)]
+pub struct Ripemd160 {
+    state: [u32; 5],
+    buffer: [u8; RIPEMD160_BLOCK_BYTES],
+    buflen: usize,
+    // message length in bytes
+    length: u64,
+}
+
+/// The boolean function of round j / 16, the right line goes through them in
+/// reverse order.
+fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+    match round {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+impl Ripemd160 {
+    pub fn new() -> Self {
+        Self {
+            state: RIPEMD160_IV,
+            buffer: [0; RIPEMD160_BLOCK_BYTES],
+            buflen: 0,
+            length: 0,
+        }
+    }
+
+    fn compress(&mut self, block: &[u8; RIPEMD160_BLOCK_BYTES]) {
+        let mut x = [0u32; 16];
+        for i in 0..16 {
+            x[i] = u32::from_le_bytes(block[i * 4..i * 4 + 4].try_into().expect("len 4"));
+        }
+
+        // two independent lines over the same message, combined at the end
+        let [mut a, mut b, mut c, mut d, mut e] = self.state;
+        let [mut ap, mut bp, mut cp, mut dp, mut ep] = self.state;
+        for j in 0..80 {
+            let round = j / 16;
+
+            let t = a
+                .wrapping_add(f(round, b, c, d))
+                .wrapping_add(x[RIPEMD160_R[j]])
+                .wrapping_add(RIPEMD160_K[round])
+                .rotate_left(RIPEMD160_S[j])
+                .wrapping_add(e);
+            a = e;
+            e = d;
+            d = c.rotate_left(10);
+            c = b;
+            b = t;
+
+            let t = ap
+                .wrapping_add(f(4 - round, bp, cp, dp))
+                .wrapping_add(x[RIPEMD160_R_PRIME[j]])
+                .wrapping_add(RIPEMD160_K_PRIME[round])
+                .rotate_left(RIPEMD160_S_PRIME[j])
+                .wrapping_add(ep);
+            ap = ep;
+            ep = dp;
+            dp = cp.rotate_left(10);
+            cp = bp;
+            bp = t;
+        }
+
+        let [h0, h1, h2, h3, h4] = self.state;
+        self.state = [
+            h1.wrapping_add(c).wrapping_add(dp),
+            h2.wrapping_add(d).wrapping_add(ep),
+            h3.wrapping_add(e).wrapping_add(ap),
+            h4.wrapping_add(a).wrapping_add(bp),
+            h0.wrapping_add(b).wrapping_add(cp),
+        ];
+    }
+
+    fn finalize(mut self) -> [u8; 20] {
+        // the same padding as MD5, with the length in little endian
+        let bit_length = self.length.wrapping_mul(8);
+        let mut padding = [0u8; RIPEMD160_BLOCK_BYTES];
+        padding[0] = 0x80;
+        let zeros = (RIPEMD160_BLOCK_BYTES * 2 - 8 - 1 - self.buflen) % RIPEMD160_BLOCK_BYTES;
+        self.update(&padding[..1 + zeros]);
+        self.update(&bit_length.to_le_bytes());
+        debug_assert_eq!(self.buflen, 0);
+
+        let mut result = [0; 20];
+        for (bytes, word) in result.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        result
+    }
+}
+
+impl Default for Ripemd160 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Ripemd160 {
+    fn update(&mut self, mut message: &[u8]) {
+        self.length += message.len() as u64;
+
+        while !message.is_empty() {
+            let take = (RIPEMD160_BLOCK_BYTES - self.buflen).min(message.len());
+            self.buffer[self.buflen..self.buflen + take].copy_from_slice(&message[..take]);
+            self.buflen += take;
+            message = &message[take..];
+
+            if self.buflen == RIPEMD160_BLOCK_BYTES {
+                let block = self.buffer;
+                self.compress(&block);
+                self.buflen = 0;
+            }
+        }
+    }
+
+    fn digest(self) -> Vec<u8> {
+        self.finalize().to_vec()
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Ripemd160 {
+    const BLOCK_SIZE: usize = RIPEMD160_BLOCK_BYTES;
+    const OUTPUT_SIZE: usize = 20;
+
+    fn finalize_into(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.finalize());
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// HASH160 from Bitcoin, RIPEMD-160 of SHA-256. P2PKH and P2WPKH addresses
+/// are built from the HASH160 of the public key.
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    let mut hasher = Ripemd160::new();
+    hasher.update(&Sha256::hash(data));
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::{HashFunction, Ripemd160, hash160},
+        slice::{decode_hex, u8_to_hexstr},
+    };
+
+    #[test]
+    fn basic_ripemd160() {
+        // the test vectors from the RIPEMD-160 paper
+        let cases: [(&[u8], &str); 8] = [
+            (b"", "9c1185a5c5e9fc54612808977ee8f548b2258d31"),
+            (b"a", "0bdc9d2d256b3ee9daae347be6f4dc835a467ffe"),
+            (b"abc", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+            (
+                b"message digest",
+                "5d0689ef49d2fae572b881b123a85ffa21595f36",
+            ),
+            (
+                b"abcdefghijklmnopqrstuvwxyz",
+                "f71c27109c692c1b56bbdceb5b9d2865b3708dbc",
+            ),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "12a053384a9c0c88e405a06c27dcf49ada62eb2b",
+            ),
+            (
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "b0e20b6e3116640286ed3a87a5713079b21f5189",
+            ),
+            (
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "9b752e45573d4b39f4dbd3323cab82bf63326bfb",
+            ),
+        ];
+
+        for (message, expected) in cases {
+            assert_eq!(u8_to_hexstr(&Ripemd160::hash(message)), expected);
+        }
+    }
+
+    #[test]
+    fn ripemd160_million_a() {
+        let mut hasher = Ripemd160::new();
+        for _ in 0..1000 {
+            hasher.update(&[b'a'; 1000]);
+        }
+        let res1 = u8_to_hexstr(&hasher.digest());
+        let exp1 = "52783243c1697bdbe16d37f97f68f08325dc1528";
+        assert_eq!(res1, exp1);
+    }
+
+    #[test]
+    fn basic_hash160() {
+        // the compressed public key from the Bitcoin wiki address example
+        let public_key =
+            decode_hex("0250863ad64a87ae8a2fe83c1af1a8403cb53f53e486d8511dad8a04887e5b2352");
+        let res1 = u8_to_hexstr(&hash160(&public_key));
+        let exp1 = "f54a5851e9372b87810a8e60cdd2e7cfd80b6e31";
+        assert_eq!(res1, exp1);
+    }
+}
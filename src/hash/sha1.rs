@@ -0,0 +1,402 @@
+use self::ubc_check::{SHA1_DVS, ubc_check};
+use super::{DigestError, FixedOutputHash, HashFunction};
+
+mod ubc_check;
+
+const SHA1_IV: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+// sqrt(2), sqrt(3), sqrt(5) and sqrt(10) times 2^30
+const SHA1_K: [u32; 4] = [0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xca62c1d6];
+
+const SHA1_BLOCK_BYTES: usize = 64;
+
+/// SHA-1 with the counter-cryptanalysis of sha1collisiondetection: every
+/// block is checked for the message differences of the known near-collision
+/// attacks. Normal messages get the normal SHA-1 hash. When an attack block
+/// is found, digest() gives the "safe hash" of sha1collisiondetection
+/// instead, with that block compressed three times, so that the colliding
+/// messages get different hashes. try_digest() reports the attack.
+#[derive(Clone)]
+pub struct Sha1 {
+    state: [u32; 5],
+    buffer: [u8; SHA1_BLOCK_BYTES],
+    buflen: usize,
+    // message length in bytes
+    length: u64,
+    // number of compressed blocks, for the offset in the error
+    blocks: u64,
+    collision: Option<DigestError>,
+}
+
+fn f(i: usize, b: u32, c: u32, d: u32) -> u32 {
+    match i / 20 {
+        0 => (b & c) | (!b & d),
+        2 => (b & c) | (b & d) | (c & d),
+        _ => b ^ c ^ d,
+    }
+}
+
+/// SHA-1 step i, from the state before it to the state after it.
+fn step(s: [u32; 5], i: usize, w: u32) -> [u32; 5] {
+    let [a, b, c, d, e] = s;
+    let t = a
+        .rotate_left(5)
+        .wrapping_add(f(i, b, c, d))
+        .wrapping_add(e)
+        .wrapping_add(SHA1_K[i / 20])
+        .wrapping_add(w);
+    [t, a, b.rotate_left(30), c, d]
+}
+
+/// SHA-1 step i backwards, from the state after it to the state before it.
+fn step_back(s: [u32; 5], i: usize, w: u32) -> [u32; 5] {
+    let [t, a, b, c, d] = s;
+    let b = b.rotate_right(30);
+    let e = t
+        .wrapping_sub(a.rotate_left(5))
+        .wrapping_sub(f(i, b, c, d))
+        .wrapping_sub(SHA1_K[i / 20])
+        .wrapping_sub(w);
+    [a, b, c, d, e]
+}
+
+fn expand(w: &mut [u32; 80]) {
+    for i in 16..80 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+}
+
+/// One compression of the expanded block w into state, without detection.
+fn compress_expanded(state: &mut [u32; 5], w: &[u32; 80]) {
+    let s = (0..80).fold(*state, |s, i| step(s, i, w[i]));
+    for (h, v) in state.iter_mut().zip(s) {
+        *h = h.wrapping_add(v);
+    }
+}
+
+impl Sha1 {
+    pub fn new() -> Self {
+        Self {
+            state: SHA1_IV,
+            buffer: [0; SHA1_BLOCK_BYTES],
+            buflen: 0,
+            length: 0,
+            blocks: 0,
+            collision: None,
+        }
+    }
+
+    fn compress(&mut self, block: &[u8; SHA1_BLOCK_BYTES]) {
+        let mut w = [0u32; 80];
+        for i in 0..16 {
+            w[i] = u32::from_be_bytes(block[i * 4..i * 4 + 4].try_into().expect("len 4"));
+        }
+        expand(&mut w);
+
+        // the states before steps 58 and 65 are where the attacks have no
+        // state difference, recompression starts from there
+        let mut states = [[0u32; 5]; 80];
+        let mut s = self.state;
+        for i in 0..80 {
+            states[i] = s;
+            s = step(s, i, w[i]);
+        }
+
+        for (h, v) in self.state.iter_mut().zip(s) {
+            *h = h.wrapping_add(v);
+        }
+
+        if self.is_attack_block(&w, &states) {
+            if self.collision.is_none() {
+                self.collision = Some(DigestError::Collision {
+                    offset: self.blocks * SHA1_BLOCK_BYTES as u64,
+                });
+            }
+            // the safe hash: two more compressions of the same block
+            compress_expanded(&mut self.state, &w);
+            compress_expanded(&mut self.state, &w);
+        }
+        self.blocks += 1;
+    }
+
+    /// For every disturbance vector whose bit conditions hold, compress the
+    /// block with the attack's message difference applied instead, starting
+    /// from the state in the middle. If the other block reaches the same
+    /// chaining value, this block is one half of a collision.
+    fn is_attack_block(&self, w: &[u32; 80], states: &[[u32; 5]; 80]) -> bool {
+        let mask = ubc_check(w);
+
+        for dv in SHA1_DVS.iter().filter(|dv| mask & dv.mask != 0) {
+            let mut dm = [0u32; 80];
+            dm[..16].copy_from_slice(&dv.dm);
+            expand(&mut dm);
+
+            let mut w2 = [0u32; 80];
+            for i in 0..80 {
+                w2[i] = w[i] ^ dm[i];
+            }
+
+            let mut s = states[dv.step];
+            for i in (0..dv.step).rev() {
+                s = step_back(s, i, w2[i]);
+            }
+            let ihv_in2 = s;
+
+            let mut s = states[dv.step];
+            for i in dv.step..80 {
+                s = step(s, i, w2[i]);
+            }
+
+            if (0..5).all(|i| ihv_in2[i].wrapping_add(s[i]) == self.state[i]) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Pads the message and returns the (safe) hash, self.collision tells
+    /// whether it differs from plain SHA-1.
+    fn finish(&mut self) -> [u8; 20] {
+        // pad with a 1 bit, zeros, and the message length in bits (big endian)
+        let bit_length = self.length.wrapping_mul(8);
+        let mut padding = [0u8; SHA1_BLOCK_BYTES];
+        padding[0] = 0x80;
+        let zeros = (SHA1_BLOCK_BYTES * 2 - 8 - 1 - self.buflen) % SHA1_BLOCK_BYTES;
+        self.update(&padding[..1 + zeros]);
+        self.update(&bit_length.to_be_bytes());
+        debug_assert_eq!(self.buflen, 0);
+
+        let mut result = [0; 20];
+        for (bytes, word) in result.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        result
+    }
+
+    fn try_finish(mut self) -> Result<[u8; 20], DigestError> {
+        let result = self.finish();
+        match self.collision {
+            Some(error) => Err(error),
+            None => Ok(result),
+        }
+    }
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Sha1 {
+    fn update(&mut self, mut message: &[u8]) {
+        self.length += message.len() as u64;
+
+        while !message.is_empty() {
+            let take = (SHA1_BLOCK_BYTES - self.buflen).min(message.len());
+            self.buffer[self.buflen..self.buflen + take].copy_from_slice(&message[..take]);
+            self.buflen += take;
+            message = &message[take..];
+
+            if self.buflen == SHA1_BLOCK_BYTES {
+                let block = self.buffer;
+                self.compress(&block);
+                self.buflen = 0;
+            }
+        }
+    }
+
+    /// The safe hash when a collision attack is detected, use try_digest()
+    /// to handle that case.
+    fn digest(mut self) -> Vec<u8> {
+        self.finish().to_vec()
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+
+    fn try_digest(self) -> Result<Vec<u8>, DigestError> {
+        self.try_finish().map(|result| result.to_vec())
+    }
+}
+
+impl FixedOutputHash for Sha1 {
+    const BLOCK_SIZE: usize = SHA1_BLOCK_BYTES;
+    const OUTPUT_SIZE: usize = 20;
+
+    /// The safe hash when a collision attack is detected, like digest().
+    fn finalize_into(mut self, out: &mut [u8]) {
+        out.copy_from_slice(&self.finish());
+    }
+
+    fn try_finalize_into(self, out: &mut [u8]) -> Result<(), DigestError> {
+        out.copy_from_slice(&self.try_finish()?);
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use crate::{
+        hash::{DigestError, DynHash, FixedOutputHash, HashFunction, Sha1, hash_reader},
+        kdf::{pbkdf2, try_pbkdf2},
+        mac::{Hmac, MacError},
+        merkle::MerkleTree,
+        slice::u8_to_hexstr,
+    };
+
+    // the first 320 bytes of the SHAttered PDFs, the collision is in the
+    // last two blocks
+    const SHATTERED_1: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/sha1/shattered-1.bin"
+    ));
+    const SHATTERED_2: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/sha1/shattered-2.bin"
+    ));
+    // the chosen-prefix collision from "SHA-1 is a Shambles"
+    const SHAMBLES_1: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/sha1/sha-mbles-1.bin"
+    ));
+    const SHAMBLES_2: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/sha1/sha-mbles-2.bin"
+    ));
+
+    #[test]
+    fn basic_sha1() {
+        let res1 = u8_to_hexstr(&Sha1::hash(b""));
+        let exp1 = "da39a3ee5e6b4b0d3255bfef95601890afd80709";
+        assert_eq!(res1, exp1);
+
+        let res2 = u8_to_hexstr(&Sha1::hash(b"abc"));
+        let exp2 = "a9993e364706816aba3e25717850c26c9cd0d89d";
+        assert_eq!(res2, exp2);
+
+        let res3 = u8_to_hexstr(&Sha1::hash(
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+        ));
+        let exp3 = "84983e441c3bd26ebaae4aa1f95129e5e54670f1";
+        assert_eq!(res3, exp3);
+
+        let res4 = u8_to_hexstr(&Sha1::hash(&vec![b'a'; 1_000_000]));
+        let exp4 = "34aa973cd4c4daa4f61eeb2bdbad27316534016f";
+        assert_eq!(res4, exp4);
+    }
+
+    #[test]
+    fn sha1_streaming() {
+        let message: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+        let mut hasher = Sha1::new();
+        for chunk in message.chunks(7) {
+            hasher.update(chunk);
+        }
+        let res1 = u8_to_hexstr(&hasher.try_digest().unwrap());
+        let exp1 = "c9c960a0b925474fab83942cc27d504fc24ac37b";
+        assert_eq!(res1, exp1);
+    }
+
+    #[test]
+    fn sha1_detects_shattered() {
+        for file in [SHATTERED_1, SHATTERED_2] {
+            let mut hasher = Sha1::new();
+            hasher.update(file);
+            assert_eq!(
+                hasher.try_digest(),
+                Err(DigestError::Collision { offset: 256 })
+            );
+        }
+    }
+
+    #[test]
+    fn sha1_detects_shambles() {
+        for file in [SHAMBLES_1, SHAMBLES_2] {
+            let mut hasher = Sha1::new();
+            hasher.update(file);
+            assert!(hasher.try_digest().is_err());
+        }
+    }
+
+    #[test]
+    fn sha1_collision_through_generic_apis() {
+        let error = DigestError::Collision { offset: 256 };
+
+        let res1 = hash_reader(Sha1::new(), SHATTERED_1).unwrap_err();
+        assert_eq!(res1.kind(), ErrorKind::InvalidData);
+        assert_eq!(res1.into_inner().unwrap().downcast_ref(), Some(&error));
+
+        let mut hasher = DynHash::new(Sha1::new());
+        hasher.update(SHATTERED_2);
+        assert_eq!(hasher.try_digest(), Err(error));
+
+        // a key that makes the inner hash start with the first block of the PDF
+        let key: Vec<u8> = SHATTERED_1[..64].iter().map(|b| b ^ 0x36).collect();
+        let mut mac = Hmac::<Sha1>::new(&key);
+        mac.update(&SHATTERED_1[64..]);
+        assert_eq!(mac.clone().try_finalize(), Err(error));
+        assert_eq!(mac.verify(&[0; 20]), Err(MacError));
+
+        // the same key, with the rest of the PDF as the salt
+        let mut out = [0; 20];
+        let res2 = try_pbkdf2::<Sha1>(&key, &SHATTERED_1[64..], 1, &mut out);
+        assert_eq!(res2, Err(error));
+
+        let mut hasher = Sha1::new();
+        hasher.update(SHATTERED_1);
+        assert_eq!(hasher.try_finalize_into(&mut out), Err(error));
+    }
+
+    #[test]
+    fn sha1_safe_hash() {
+        // both prefixes have the plain SHA-1 hash
+        // f92d74e3874587aaf443d1db961d4e26dde13e9c, the safe hash compresses
+        // the attack block at byte 256 three times
+        let res1 = u8_to_hexstr(&Sha1::hash(SHATTERED_1));
+        let exp1 = "7117b3cb9225aaf0d8ef1a40e493957b0bf8693d";
+        assert_eq!(res1, exp1);
+        let res2 = u8_to_hexstr(&Sha1::hash(SHATTERED_2));
+        let exp2 = "29f38ae9fd98e2931120fa0bf213e024250d3f6a";
+        assert_eq!(res2, exp2);
+
+        let mut out = [0; 20];
+        let mut hasher = Sha1::new();
+        hasher.update(SHATTERED_2);
+        hasher.finalize_into(&mut out);
+        assert_eq!(u8_to_hexstr(&out), exp2);
+
+        // generic callers get different results for the two files
+        let key: Vec<u8> = SHATTERED_1[..64].iter().map(|b| b ^ 0x36).collect();
+        let tags = [SHATTERED_1, SHATTERED_2].map(|file| {
+            let mut mac = Hmac::<Sha1>::new(&key);
+            mac.update(&file[64..]);
+            mac.finalize()
+        });
+        assert_ne!(tags[0], tags[1]);
+
+        let keys = [SHATTERED_1, SHATTERED_2].map(|file| {
+            let mut out = [0; 20];
+            pbkdf2::<Sha1>(&key, &file[64..], 2, &mut out);
+            out
+        });
+        assert_ne!(keys[0], keys[1]);
+
+        let roots = [SHATTERED_1, SHATTERED_2].map(|file| {
+            let mut tree = MerkleTree::with_hasher(Sha1::new());
+            tree.push(file);
+            tree.root()
+        });
+        assert_ne!(roots[0], roots[1]);
+    }
+}
@@ -0,0 +1,920 @@
+use super::{FixedOutputHash, HashFunction};
+
+// Frac(cbrt(p)) of the first 64 primes
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// Frac(cbrt(p)) of the first 80 primes
+const SHA512_K: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+// Frac(sqrt(p)) of the first 8 primes, the same IV as Blake2s
+const SHA256_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+// the low halves of the SHA-384 IV
+const SHA224_IV: [u32; 8] = [
+    0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4,
+];
+
+// Frac(sqrt(p)) of the first 8 primes, the same IV as Blake2b
+const SHA512_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+// Frac(sqrt(p)) of the 9th to 16th primes
+const SHA384_IV: [u64; 8] = [
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4,
+];
+
+// SHA-512/t IVs are generated by hashing "SHA-512/t" (FIPS 180-4 section 5.3.6)
+const SHA512_224_IV: [u64; 8] = [
+    0x8c3d37c819544da2,
+    0x73e1996689dcd4d6,
+    0x1dfab7ae32ff9c82,
+    0x679dd514582f9fcf,
+    0x0f6d2b697bd44da8,
+    0x77e36f7304c48942,
+    0x3f9d85a86a1d36c8,
+    0x1112e6ad91d692a1,
+];
+
+const SHA512_256_IV: [u64; 8] = [
+    0x22312194fc2bf72c,
+    0x9f555fa3c84c64c2,
+    0x2393b86b6f53b151,
+    0x963877195940eabd,
+    0x96283ee2a88effe3,
+    0xbe5e1e2553863992,
+    0x2b0199fc2c85b8aa,
+    0x0eb72ddc81c52ca2,
+];
+
+const SHA256_BLOCK_BYTES: usize = 64;
+const SHA512_BLOCK_BYTES: usize = 128;
+
+/// The SHA-256 compression function and padding, shared by SHA-224.
+#[derive(Clone)]
+struct Sha256Core {
+    state: [u32; 8],
+    buffer: [u8; SHA256_BLOCK_BYTES],
+    buflen: usize,
+    // message length in bytes
+    length: u64,
+}
+
+impl Sha256Core {
+    fn new(iv: [u32; 8]) -> Self {
+        Self {
+            state: iv,
+            buffer: [0; SHA256_BLOCK_BYTES],
+            buflen: 0,
+            length: 0,
+        }
+    }
+
+    fn compress(&mut self, block: &[u8; SHA256_BLOCK_BYTES]) {
+        let mut w = [0u32; 64];
+        for i in 0..16 {
+            w[i] = u32::from_be_bytes(block[i * 4..i * 4 + 4].try_into().expect("len 4"));
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA256_K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (s, v) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+
+    fn update(&mut self, mut message: &[u8]) {
+        self.length += message.len() as u64;
+
+        // unlike Blake2, the last block is marked by the padding, so full
+        // blocks can be compressed right away
+        while !message.is_empty() {
+            let take = (SHA256_BLOCK_BYTES - self.buflen).min(message.len());
+            self.buffer[self.buflen..self.buflen + take].copy_from_slice(&message[..take]);
+            self.buflen += take;
+            message = &message[take..];
+
+            if self.buflen == SHA256_BLOCK_BYTES {
+                let block = self.buffer;
+                self.compress(&block);
+                self.buflen = 0;
+            }
+        }
+    }
+
+    fn finalize(mut self) -> [u8; 32] {
+        // pad with a 1 bit, zeros, and the message length in bits (big endian)
+        let bit_length = self.length.wrapping_mul(8);
+        let mut padding = [0u8; SHA256_BLOCK_BYTES + 8];
+        padding[0] = 0x80;
+        let zeros = (SHA256_BLOCK_BYTES * 2 - 8 - 1 - self.buflen) % SHA256_BLOCK_BYTES;
+        self.update(&padding[..1 + zeros]);
+        self.update(&bit_length.to_be_bytes());
+        debug_assert_eq!(self.buflen, 0);
+
+        let mut result = [0; 32];
+        for (bytes, word) in result.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        result
+    }
+}
+
+/// The SHA-512 compression function and padding, shared by SHA-384 and
+/// SHA-512/t.
+#[derive(Clone)]
+struct Sha512Core {
+    state: [u64; 8],
+    buffer: [u8; SHA512_BLOCK_BYTES],
+    buflen: usize,
+    // message length in bytes
+    length: u128,
+}
+
+impl Sha512Core {
+    fn new(iv: [u64; 8]) -> Self {
+        Self {
+            state: iv,
+            buffer: [0; SHA512_BLOCK_BYTES],
+            buflen: 0,
+            length: 0,
+        }
+    }
+
+    fn compress(&mut self, block: &[u8; SHA512_BLOCK_BYTES]) {
+        let mut w = [0u64; 80];
+        for i in 0..16 {
+            w[i] = u64::from_be_bytes(block[i * 8..i * 8 + 8].try_into().expect("len 8"));
+        }
+        for i in 16..80 {
+            let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+            let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for i in 0..80 {
+            let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA512_K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (s, v) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+
+    fn update(&mut self, mut message: &[u8]) {
+        self.length += message.len() as u128;
+
+        while !message.is_empty() {
+            let take = (SHA512_BLOCK_BYTES - self.buflen).min(message.len());
+            self.buffer[self.buflen..self.buflen + take].copy_from_slice(&message[..take]);
+            self.buflen += take;
+            message = &message[take..];
+
+            if self.buflen == SHA512_BLOCK_BYTES {
+                let block = self.buffer;
+                self.compress(&block);
+                self.buflen = 0;
+            }
+        }
+    }
+
+    fn finalize(mut self) -> [u8; 64] {
+        // pad with a 1 bit, zeros, and the message length in bits (big endian)
+        let bit_length = self.length.wrapping_mul(8);
+        let mut padding = [0u8; SHA512_BLOCK_BYTES + 16];
+        padding[0] = 0x80;
+        let zeros = (SHA512_BLOCK_BYTES * 2 - 16 - 1 - self.buflen) % SHA512_BLOCK_BYTES;
+        self.update(&padding[..1 + zeros]);
+        self.update(&bit_length.to_be_bytes());
+        debug_assert_eq!(self.buflen, 0);
+
+        let mut result = [0; 64];
+        for (bytes, word) in result.chunks_exact_mut(8).zip(self.state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        result
+    }
+}
+
+/// SHA-224, SHA-256 with a different IV and a truncated output.
+#[derive(Clone)]
+pub struct Sha224 {
+    core: Sha256Core,
+}
+
+impl Sha224 {
+    pub fn new() -> Self {
+        Self {
+            core: Sha256Core::new(SHA224_IV),
+        }
+    }
+}
+
+impl Default for Sha224 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Sha224 {
+    fn update(&mut self, message: &[u8]) {
+        self.core.update(message);
+    }
+
+    fn digest(self) -> Vec<u8> {
+        self.core.finalize()[..28].to_vec()
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Sha224 {
+    const BLOCK_SIZE: usize = SHA256_BLOCK_BYTES;
+    const OUTPUT_SIZE: usize = 28;
+
+    fn finalize_into(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.core.finalize()[..28]);
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[derive(Clone)]
+pub struct Sha256 {
+    core: Sha256Core,
+}
+
+impl Sha256 {
+    pub fn new() -> Self {
+        Self {
+            core: Sha256Core::new(SHA256_IV),
+        }
+    }
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Sha256 {
+    fn update(&mut self, message: &[u8]) {
+        self.core.update(message);
+    }
+
+    fn digest(self) -> Vec<u8> {
+        self.core.finalize().to_vec()
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Sha256 {
+    const BLOCK_SIZE: usize = SHA256_BLOCK_BYTES;
+    const OUTPUT_SIZE: usize = 32;
+
+    fn finalize_into(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.core.finalize());
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// SHA-384, SHA-512 with a different IV and a truncated output.
+#[derive(Clone)]
+pub struct Sha384 {
+    core: Sha512Core,
+}
+
+impl Sha384 {
+    pub fn new() -> Self {
+        Self {
+            core: Sha512Core::new(SHA384_IV),
+        }
+    }
+}
+
+impl Default for Sha384 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Sha384 {
+    fn update(&mut self, message: &[u8]) {
+        self.core.update(message);
+    }
+
+    fn digest(self) -> Vec<u8> {
+        self.core.finalize()[..48].to_vec()
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Sha384 {
+    const BLOCK_SIZE: usize = SHA512_BLOCK_BYTES;
+    const OUTPUT_SIZE: usize = 48;
+
+    fn finalize_into(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.core.finalize()[..48]);
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[derive(Clone)]
+pub struct Sha512 {
+    core: Sha512Core,
+}
+
+impl Sha512 {
+    pub fn new() -> Self {
+        Self {
+            core: Sha512Core::new(SHA512_IV),
+        }
+    }
+}
+
+impl Default for Sha512 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Sha512 {
+    fn update(&mut self, message: &[u8]) {
+        self.core.update(message);
+    }
+
+    fn digest(self) -> Vec<u8> {
+        self.core.finalize().to_vec()
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Sha512 {
+    const BLOCK_SIZE: usize = SHA512_BLOCK_BYTES;
+    const OUTPUT_SIZE: usize = 64;
+
+    fn finalize_into(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.core.finalize());
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// SHA-512/224, SHA-512 with its own IV and a 28 byte output.
+#[derive(Clone)]
+pub struct Sha512_224 {
+    core: Sha512Core,
+}
+
+impl Sha512_224 {
+    pub fn new() -> Self {
+        Self {
+            core: Sha512Core::new(SHA512_224_IV),
+        }
+    }
+}
+
+impl Default for Sha512_224 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Sha512_224 {
+    fn update(&mut self, message: &[u8]) {
+        self.core.update(message);
+    }
+
+    fn digest(self) -> Vec<u8> {
+        self.core.finalize()[..28].to_vec()
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Sha512_224 {
+    const BLOCK_SIZE: usize = SHA512_BLOCK_BYTES;
+    const OUTPUT_SIZE: usize = 28;
+
+    fn finalize_into(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.core.finalize()[..28]);
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// SHA-512/256, SHA-512 with its own IV and a 32 byte output.
+#[derive(Clone)]
+pub struct Sha512_256 {
+    core: Sha512Core,
+}
+
+impl Sha512_256 {
+    pub fn new() -> Self {
+        Self {
+            core: Sha512Core::new(SHA512_256_IV),
+        }
+    }
+}
+
+impl Default for Sha512_256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Sha512_256 {
+    fn update(&mut self, message: &[u8]) {
+        self.core.update(message);
+    }
+
+    fn digest(self) -> Vec<u8> {
+        self.core.finalize()[..32].to_vec()
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Sha512_256 {
+    const BLOCK_SIZE: usize = SHA512_BLOCK_BYTES;
+    const OUTPUT_SIZE: usize = 32;
+
+    fn finalize_into(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.core.finalize()[..32]);
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::{
+            HashFunction, Sha224, Sha256, Sha384, Sha512, Sha512_224, Sha512_256, test_util::check,
+        },
+        slice::{decode_hex, u8_to_hexstr},
+    };
+
+    // the FIPS 180-4 example messages: one block, two blocks for SHA-256
+    // (the 448 bit message), and two blocks for SHA-512 (the 896 bit message)
+    const ABC: &[u8] = b"abc";
+    const MSG_448: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    const MSG_896: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    /// The repeated 'a' long message from the FIPS 180 examples.
+    fn million_a() -> Vec<u8> {
+        vec![b'a'; 1_000_000]
+    }
+
+    #[test]
+    fn basic_sha224() {
+        check(
+            Sha224::new,
+            b"",
+            "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f",
+        );
+        check(
+            Sha224::new,
+            ABC,
+            "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+        );
+        check(
+            Sha224::new,
+            MSG_448,
+            "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525",
+        );
+        check(
+            Sha224::new,
+            MSG_896,
+            "c97ca9a559850ce97a04a96def6d99a9e0e0e2ab14e6b8df265fc0b3",
+        );
+        // SHA224ShortMsg.rsp, Len = 8
+        check(
+            Sha224::new,
+            &decode_hex("84"),
+            "3cd36921df5d6963e73739cf4d20211e2d8877c19cff087ade9d0e3a",
+        );
+        // SHA224LongMsg.rsp, Len = 1304
+        check(
+            Sha224::new,
+            &decode_hex(
+                "f149e41d848f59276cfddd743bafa9a90e1ee4a263a118142b33e3702176ef0a59f8237a1cb51b42f3ded6b202d9af0997898fdd03cf60bda951c514547a0850cec25444ae2f24cb711bfbafcc3956c941d3de69f155e3f8b10f06db5f37359b772ddd43e1035a0a0d3db33242d5843033833b0dd43b870c6bf60e8deab55f317cc3273f5e3ba747f0cb65050cb7228796210d9254873643008d45f29cfd6c5b060c9a",
+            ),
+            "9db6dc3a23abd7b6c3d72c38f4843c7de48a71d0ba91a86b18393e5f",
+        );
+
+        let res = u8_to_hexstr(&Sha224::hash(&million_a()));
+        assert_eq!(
+            res,
+            "20794655980c91d8bbb4c1ea97618a4bf03f42581948b2ee4ee7ad67"
+        );
+    }
+
+    #[test]
+    fn basic_sha256() {
+        check(
+            Sha256::new,
+            b"",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        );
+        check(
+            Sha256::new,
+            ABC,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        );
+        check(
+            Sha256::new,
+            MSG_448,
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+        );
+        check(
+            Sha256::new,
+            MSG_896,
+            "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
+        );
+        // SHA256ShortMsg.rsp, Len = 8 and Len = 16
+        check(
+            Sha256::new,
+            &decode_hex("d3"),
+            "28969cdfa74a12c82f3bad960b0b000aca2ac329deea5c2328ebc6f2ba9802c1",
+        );
+        check(
+            Sha256::new,
+            &decode_hex("11af"),
+            "5ca7133fa735326081558ac312c620eeca9970d1e70a4b95533d956f072d1f98",
+        );
+        // SHA256LongMsg.rsp, Len = 1304
+        check(
+            Sha256::new,
+            &decode_hex(
+                "451101250ec6f26652249d59dc974b7361d571a8101cdfd36aba3b5854d3ae086b5fdd4597721b66e3c0dc5d8c606d9657d0e323283a5217d1f53f2f284f57b85c8a61ac8924711f895c5ed90ef17745ed2d728abd22a5f7a13479a462d71b56c19a74a40b655c58edfe0a188ad2cf46cbf30524f65d423c837dd1ff2bf462ac4198007345bb44dbb7b1c861298cdf61982a833afc728fae1eda2f87aa2c9480858bec",
+            ),
+            "3c593aa539fdcdae516cdf2f15000f6634185c88f505b39775fb9ab137a10aa2",
+        );
+
+        let res = u8_to_hexstr(&Sha256::hash(&million_a()));
+        assert_eq!(
+            res,
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    #[test]
+    fn basic_sha384() {
+        check(
+            Sha384::new,
+            b"",
+            "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b",
+        );
+        check(
+            Sha384::new,
+            ABC,
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+        );
+        check(
+            Sha384::new,
+            MSG_896,
+            "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039",
+        );
+        // SHA384ShortMsg.rsp, Len = 8
+        check(
+            Sha384::new,
+            &decode_hex("c5"),
+            "b52b72da75d0666379e20f9b4a79c33a329a01f06a2fb7865c9062a28c1de860ba432edfd86b4cb1cb8a75b46076e3b1",
+        );
+        // SHA384LongMsg.rsp, Len = 1816
+        check(
+            Sha384::new,
+            &decode_hex(
+                "62c6a169b9be02b3d7b471a964fc0bcc72b480d26aecb2ed460b7f50016ddaf04c51218783f3aadfdff5a04ded030d7b3fb7376b61ba30b90e2da921a4470740d63fb99fa16cc8ed81abaf8ce4016e50df81da832070372c24a80890aa3a26fa675710b8fb718266249d496f313c55d0bada101f8f56eeccee4345a8f98f60a36662cfda794900d12f9414fcbdfdeb85388a814996b47e24d5c8086e7a8edcc53d299d0d033e6bb60c58b83d6e8b57f6c258d6081dd10eb942fdf8ec157ec3e75371235a8196eb9d22b1de3a2d30c2abbe0db7650cf6c7159bacbe29b3a93c92100508",
+            ),
+            "0730e184e7795575569f87030260bb8e54498e0e5d096b18285e988d245b6f3486d1f2447d5f85bcbe59d5689fc49425",
+        );
+
+        let res = u8_to_hexstr(&Sha384::hash(&million_a()));
+        assert_eq!(
+            res,
+            "9d0e1809716474cb086e834e310a4a1ced149e9c00f248527972cec5704c2a5b07b8b3dc38ecc4ebae97ddd87f3d8985"
+        );
+    }
+
+    #[test]
+    fn basic_sha512() {
+        check(
+            Sha512::new,
+            b"",
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+        );
+        check(
+            Sha512::new,
+            ABC,
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+        );
+        check(
+            Sha512::new,
+            MSG_448,
+            "204a8fc6dda82f0a0ced7beb8e08a41657c16ef468b228a8279be331a703c33596fd15c13b1b07f9aa1d3bea57789ca031ad85c7a71dd70354ec631238ca3445",
+        );
+        check(
+            Sha512::new,
+            MSG_896,
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909",
+        );
+        // SHA512ShortMsg.rsp, Len = 8
+        check(
+            Sha512::new,
+            &decode_hex("21"),
+            "3831a6a6155e509dee59a7f451eb35324d8f8f2df6e3708894740f98fdee23889f4de5adb0c5010dfb555cda77c8ab5dc902094c52de3278f35a75ebc25f093a",
+        );
+        // SHA512LongMsg.rsp, Len = 1816
+        check(
+            Sha512::new,
+            &decode_hex(
+                "4f05600950664d5190a2ebc29c9edb89c20079a4d3e6bc3b27d75e34e2fa3d02768502bd69790078598d5fcf3d6779bfed1284bbe5ad72fb456015181d9587d6e864c940564eaafb4f2fead4346ea09b6877d9340f6b82eb1515880872213da3ad88feba9f4f13817a71d6f90a1a17c43a15c038d988b5b29edffe2d6a062813cedbe852cde302b3e33b696846d2a8e36bd680efcc6cd3f9e9a4c1ae8cac10cc5244d131677140399176ed46700019a004a163806f7fa467fc4e17b4617bbd7641aaff7ff56396ba8c08a8be100b33a20b5daf134a2aefa5e1c3496770dcf6baa4f7bb",
+            ),
+            "a9db490c708cc72548d78635aa7da79bb253f945d710e5cb677a474efc7c65a2aab45bc7ca1113c8ce0f3c32e1399de9c459535e8816521ab714b2a6cd200525",
+        );
+
+        let res = u8_to_hexstr(&Sha512::hash(&million_a()));
+        assert_eq!(
+            res,
+            "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973ebde0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b"
+        );
+    }
+
+    #[test]
+    fn basic_sha512_t() {
+        check(
+            Sha512_224::new,
+            ABC,
+            "4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa",
+        );
+        check(
+            Sha512_224::new,
+            MSG_896,
+            "23fec5bb94d60b23308192640b0c453335d664734fe40e7268674af9",
+        );
+        check(
+            Sha512_256::new,
+            ABC,
+            "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23",
+        );
+        check(
+            Sha512_256::new,
+            MSG_896,
+            "3928e184fb8690f840da3988121d31be65cb9d3ef83ee6146feac861e19b563a",
+        );
+
+        let res1 = u8_to_hexstr(&Sha512_224::hash(&million_a()));
+        assert_eq!(
+            res1,
+            "37ab331d76f0d36de422bd0edeb22a28accd487b7a8453ae965dd287"
+        );
+        let res2 = u8_to_hexstr(&Sha512_256::hash(&million_a()));
+        assert_eq!(
+            res2,
+            "9a59a052930187a97038cae692f30708aa6491923ef5194394dc68d56c74fb21"
+        );
+    }
+
+    #[test]
+    fn sha2_padding_boundaries() {
+        // 55 and 111 bytes are the longest messages whose padding fits in
+        // the same block, one more byte pushes the length into a new block
+        let message: Vec<u8> = (0..=255).collect();
+        let expected_256 = [
+            (
+                55,
+                "463eb28e72f82e0a96c0a4cc53690c571281131f672aa229e0d45ae59b598b59",
+            ),
+            (
+                56,
+                "da2ae4d6b36748f2a318f23e7ab1dfdf45acdc9d049bd80e59de82a60895f562",
+            ),
+            (
+                64,
+                "fdeab9acf3710362bd2658cdc9a29e8f9c757fcf9811603a8c447cd1d9151108",
+            ),
+        ];
+        for (len, expected) in expected_256 {
+            check(Sha256::new, &message[..len], expected);
+        }
+
+        let expected_512 = [
+            (
+                111,
+                "a1a111449b198d9b1f538bad7f3fc1022b3a5b1a5e90a0bc860de8512746cbc31599e6c834de3a3235327af0b51ff57bf7acf1974a73014d9c3953812edc7c8d",
+            ),
+            (
+                112,
+                "c5fbd731d19d2ae1180f001be72c2c1aaba1d7b094b3748880e24593b8e117a750e11c1bd867cc2f96dace8c8b74abd2d5c4f236be444e77d30d1916174070b9",
+            ),
+            (
+                128,
+                "1dffd5e3adb71d45d2245939665521ae001a317a03720a45732ba1900ca3b8351fc5c9b4ca513eba6f80bc7b1d1fdad4abd13491cb824d61b08d8c0e1561b3f7",
+            ),
+        ];
+        for (len, expected) in expected_512 {
+            check(Sha512::new, &message[..len], expected);
+        }
+    }
+}
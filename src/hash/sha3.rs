@@ -0,0 +1,528 @@
+use super::{FixedOutputHash, HashFunction, KeccakSponge, XofReader};
+
+// domain separation bits, followed by the first bit of the padding
+const SHA3_DOMAIN: u8 = 0x06;
+const SHAKE_DOMAIN: u8 = 0x1f;
+const KECCAK_DOMAIN: u8 = 0x01;
+
+/// The rate of a sponge with the given security strength in bytes:
+/// the capacity is twice the security strength.
+const fn rate(security_bytes: usize) -> usize {
+    200 - 2 * security_bytes
+}
+
+/// SHA3-224, FIPS 202.
+#[derive(Clone)]
+pub struct Sha3_224 {
+    sponge: KeccakSponge,
+}
+
+impl Sha3_224 {
+    pub fn new() -> Self {
+        Self {
+            sponge: KeccakSponge::new(rate(28)),
+        }
+    }
+}
+
+impl Default for Sha3_224 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Sha3_224 {
+    fn update(&mut self, message: &[u8]) {
+        self.sponge.absorb(message);
+    }
+
+    fn digest(self) -> Vec<u8> {
+        let mut result = vec![0; 28];
+        self.finalize_into(&mut result);
+        result
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Sha3_224 {
+    const BLOCK_SIZE: usize = rate(28);
+    const OUTPUT_SIZE: usize = 28;
+
+    fn finalize_into(mut self, out: &mut [u8]) {
+        assert_eq!(out.len(), 28, "Sha3_224 output buffer has the wrong length");
+        self.sponge.finalize(SHA3_DOMAIN);
+        self.sponge.squeeze(out);
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// SHA3-256, FIPS 202.
+#[derive(Clone)]
+pub struct Sha3_256 {
+    sponge: KeccakSponge,
+}
+
+impl Sha3_256 {
+    pub fn new() -> Self {
+        Self {
+            sponge: KeccakSponge::new(rate(32)),
+        }
+    }
+}
+
+impl Default for Sha3_256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Sha3_256 {
+    fn update(&mut self, message: &[u8]) {
+        self.sponge.absorb(message);
+    }
+
+    fn digest(self) -> Vec<u8> {
+        let mut result = vec![0; 32];
+        self.finalize_into(&mut result);
+        result
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Sha3_256 {
+    const BLOCK_SIZE: usize = rate(32);
+    const OUTPUT_SIZE: usize = 32;
+
+    fn finalize_into(mut self, out: &mut [u8]) {
+        assert_eq!(out.len(), 32, "Sha3_256 output buffer has the wrong length");
+        self.sponge.finalize(SHA3_DOMAIN);
+        self.sponge.squeeze(out);
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// SHA3-384, FIPS 202.
+#[derive(Clone)]
+pub struct Sha3_384 {
+    sponge: KeccakSponge,
+}
+
+impl Sha3_384 {
+    pub fn new() -> Self {
+        Self {
+            sponge: KeccakSponge::new(rate(48)),
+        }
+    }
+}
+
+impl Default for Sha3_384 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Sha3_384 {
+    fn update(&mut self, message: &[u8]) {
+        self.sponge.absorb(message);
+    }
+
+    fn digest(self) -> Vec<u8> {
+        let mut result = vec![0; 48];
+        self.finalize_into(&mut result);
+        result
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Sha3_384 {
+    const BLOCK_SIZE: usize = rate(48);
+    const OUTPUT_SIZE: usize = 48;
+
+    fn finalize_into(mut self, out: &mut [u8]) {
+        assert_eq!(out.len(), 48, "Sha3_384 output buffer has the wrong length");
+        self.sponge.finalize(SHA3_DOMAIN);
+        self.sponge.squeeze(out);
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// SHA3-512, FIPS 202.
+#[derive(Clone)]
+pub struct Sha3_512 {
+    sponge: KeccakSponge,
+}
+
+impl Sha3_512 {
+    pub fn new() -> Self {
+        Self {
+            sponge: KeccakSponge::new(rate(64)),
+        }
+    }
+}
+
+impl Default for Sha3_512 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Sha3_512 {
+    fn update(&mut self, message: &[u8]) {
+        self.sponge.absorb(message);
+    }
+
+    fn digest(self) -> Vec<u8> {
+        let mut result = vec![0; 64];
+        self.finalize_into(&mut result);
+        result
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Sha3_512 {
+    const BLOCK_SIZE: usize = rate(64);
+    const OUTPUT_SIZE: usize = 64;
+
+    fn finalize_into(mut self, out: &mut [u8]) {
+        assert_eq!(out.len(), 64, "Sha3_512 output buffer has the wrong length");
+        self.sponge.finalize(SHA3_DOMAIN);
+        self.sponge.squeeze(out);
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Keccak-256, the submission SHA-3 was standardised from. It only differs
+/// in its padding, and is the hash used by Ethereum.
+#[derive(Clone)]
+pub struct Keccak256 {
+    sponge: KeccakSponge,
+}
+
+impl Keccak256 {
+    pub fn new() -> Self {
+        Self {
+            sponge: KeccakSponge::new(rate(32)),
+        }
+    }
+}
+
+impl Default for Keccak256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Keccak256 {
+    fn update(&mut self, message: &[u8]) {
+        self.sponge.absorb(message);
+    }
+
+    fn digest(self) -> Vec<u8> {
+        let mut result = vec![0; 32];
+        self.finalize_into(&mut result);
+        result
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+impl FixedOutputHash for Keccak256 {
+    const BLOCK_SIZE: usize = rate(32);
+    const OUTPUT_SIZE: usize = 32;
+
+    fn finalize_into(mut self, out: &mut [u8]) {
+        assert_eq!(
+            out.len(),
+            32,
+            "Keccak256 output buffer has the wrong length"
+        );
+        self.sponge.finalize(KECCAK_DOMAIN);
+        self.sponge.squeeze(out);
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// SHAKE128, the extendable-output function of FIPS 202 with 128 bits of
+/// security.
+#[derive(Clone)]
+pub struct Shake128 {
+    sponge: KeccakSponge,
+}
+
+impl Shake128 {
+    pub fn new() -> Self {
+        Self {
+            sponge: KeccakSponge::new(rate(16)),
+        }
+    }
+
+    /// Return a reader for the unbounded output stream.
+    pub fn finalize_xof(mut self) -> ShakeReader {
+        self.sponge.finalize(SHAKE_DOMAIN);
+        ShakeReader {
+            sponge: self.sponge,
+        }
+    }
+}
+
+impl Default for Shake128 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Shake128 {
+    fn update(&mut self, message: &[u8]) {
+        self.sponge.absorb(message);
+    }
+
+    /// Reads 32 bytes of output, enough for the full security strength.
+    fn digest(self) -> Vec<u8> {
+        let mut result = vec![0; 32];
+        self.finalize_xof().read(&mut result);
+        result
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+/// SHAKE256, the extendable-output function of FIPS 202 with 256 bits of
+/// security.
+#[derive(Clone)]
+pub struct Shake256 {
+    sponge: KeccakSponge,
+}
+
+impl Shake256 {
+    pub fn new() -> Self {
+        Self {
+            sponge: KeccakSponge::new(rate(32)),
+        }
+    }
+
+    /// Return a reader for the unbounded output stream.
+    pub fn finalize_xof(mut self) -> ShakeReader {
+        self.sponge.finalize(SHAKE_DOMAIN);
+        ShakeReader {
+            sponge: self.sponge,
+        }
+    }
+}
+
+impl Default for Shake256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Shake256 {
+    fn update(&mut self, message: &[u8]) {
+        self.sponge.absorb(message);
+    }
+
+    /// Reads 64 bytes of output, enough for the full security strength.
+    fn digest(self) -> Vec<u8> {
+        let mut result = vec![0; 64];
+        self.finalize_xof().read(&mut result);
+        result
+    }
+
+    fn hash(message: &[u8]) -> Vec<u8> {
+        let mut hasher = Self::new();
+        hasher.update(message);
+        hasher.digest()
+    }
+}
+
+/// The output stream of SHAKE128 and SHAKE256, and of the SP 800-185
+/// functions built on top of them.
+pub struct ShakeReader {
+    pub(super) sponge: KeccakSponge,
+}
+
+impl XofReader for ShakeReader {
+    fn read(&mut self, buf: &mut [u8]) {
+        self.sponge.squeeze(buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::{
+            HashFunction, Keccak256, Sha3_224, Sha3_256, Sha3_384, Sha3_512, Shake128, Shake256,
+            XofReader, test_util::check,
+        },
+        slice::u8_to_hexstr,
+    };
+
+    const ABC: &[u8] = b"abc";
+
+    #[test]
+    fn basic_sha3_224() {
+        check(
+            Sha3_224::new,
+            b"",
+            "6b4e03423667dbb73b6e15454f0eb1abd4597f9a1b078e3f5b5a6bc7",
+        );
+        check(
+            Sha3_224::new,
+            ABC,
+            "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf",
+        );
+        // the 1600 bit message from the NIST examples, longer than the rate
+        check(
+            Sha3_224::new,
+            &[0xa3; 200],
+            "9376816aba503f72f96ce7eb65ac095deee3be4bf9bbc2a1cb7e11e0",
+        );
+    }
+
+    #[test]
+    fn basic_sha3_256() {
+        check(
+            Sha3_256::new,
+            b"",
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+        );
+        check(
+            Sha3_256::new,
+            ABC,
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+        );
+        check(
+            Sha3_256::new,
+            &[0xa3; 200],
+            "79f38adec5c20307a98ef76e8324afbfd46cfd81b22e3973c65fa1bd9de31787",
+        );
+
+        let message: Vec<u8> = (0..768).map(|i| i as u8).collect();
+        check(
+            Sha3_256::new,
+            &message,
+            "c043b2b15d405c9f4cd92fdaef420eba6201d328fb34ec0e2c16e4981b9e4b39",
+        );
+    }
+
+    #[test]
+    fn basic_sha3_384() {
+        check(
+            Sha3_384::new,
+            b"",
+            "0c63a75b845e4f7d01107d852e4c2485c51a50aaaa94fc61995e71bbee983a2ac3713831264adb47fb6bd1e058d5f004",
+        );
+        check(
+            Sha3_384::new,
+            ABC,
+            "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b298d88cea927ac7f539f1edf228376d25",
+        );
+    }
+
+    #[test]
+    fn basic_sha3_512() {
+        check(
+            Sha3_512::new,
+            b"",
+            "a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a615b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26",
+        );
+        check(
+            Sha3_512::new,
+            ABC,
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+        );
+    }
+
+    #[test]
+    fn basic_shake() {
+        check(
+            Shake128::new,
+            b"",
+            "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26",
+        );
+        check(
+            Shake256::new,
+            ABC,
+            "483366601360a8771c6863080cc4114d8db44530f8f1e1ee4f94ea37e78b5739d5a15bef186a5386c75744c0527e1faa9f8726e462a12a4feb06bd8801e751e4",
+        );
+    }
+
+    #[test]
+    fn shake_reader() {
+        // 300 bytes squeezes past the 168 byte rate of SHAKE128
+        let exp1 = "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc844c50af32acd3f2cdd066568706f509bc1bdde58295dae3f891a9a0fca5783789a41f8611214ce612394df286a62d1a2252aa94db9c538956c717dc2bed4f232a0294c857c730aa16067ac1062f1201fb0d377cfb9cde4c63599b27f3462bba4a0ed296c801f9ff7f57302bb3076ee145f97a32ae68e76ab66c48d51675bd49acc29082f5647584e6aa01b3f5af057805f973ff8ecb8b226ac32ada6f01c1fcd4818cb006aa5b4cdb3611eb1e533c8964cacfdf31012cd3fb744d02225b988b475375faad996eb1b9176ecb0f8b2871723d6dbb804e23357e50732f5cfc904b1319795000d7361d9e5e1b77b4b8f5774aa1482cfa58f83096bdb2e06a3eed543a38919b57ecbec737f4086be";
+
+        let mut hasher = Shake128::new();
+        hasher.update(ABC);
+        let mut reader = hasher.finalize_xof();
+        let mut res1 = [0u8; 300];
+        reader.read(&mut res1);
+        assert_eq!(u8_to_hexstr(&res1), exp1);
+
+        // reading in uneven pieces gives the same stream
+        let mut hasher = Shake128::new();
+        hasher.update(ABC);
+        let mut reader = hasher.finalize_xof();
+        let mut res2 = [0u8; 300];
+        for piece in res2.chunks_mut(7) {
+            reader.read(piece);
+        }
+        assert_eq!(u8_to_hexstr(&res2), exp1);
+    }
+
+    #[test]
+    fn basic_keccak256() {
+        check(
+            Keccak256::new,
+            b"",
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+        );
+        check(
+            Keccak256::new,
+            ABC,
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+        );
+    }
+}
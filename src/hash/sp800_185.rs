@@ -0,0 +1,557 @@
+use std::thread;
+
+use super::{KeccakSponge, ShakeReader, XofReader};
+use crate::{mac::MacError, slice::ct_eq};
+
+const CSHAKE_DOMAIN: u8 = 0x04;
+const SHAKE_DOMAIN: u8 = 0x1f;
+// the SHAKE128 and SHAKE256 rates
+const RATE_128: usize = 168;
+const RATE_256: usize = 136;
+
+/// left_encode (SP 800-185 section 2.3.1): the number of bytes of x, then x
+/// in big endian without leading zero bytes.
+fn left_encode(x: u64) -> Vec<u8> {
+    let bytes = x.to_be_bytes();
+    let skip = bytes.iter().take_while(|b| **b == 0).count().min(7);
+    let mut encoded = vec![(8 - skip) as u8];
+    encoded.extend_from_slice(&bytes[skip..]);
+    encoded
+}
+
+/// right_encode: like left_encode, but with the length byte last.
+fn right_encode(x: u64) -> Vec<u8> {
+    let mut encoded = left_encode(x);
+    encoded.rotate_left(1);
+    encoded
+}
+
+/// Absorbs encode_string(s), the bit length of s followed by s.
+fn absorb_string(sponge: &mut KeccakSponge, s: &[u8]) {
+    sponge.absorb(&left_encode(s.len() as u64 * 8));
+    sponge.absorb(s);
+}
+
+/// Absorbs bytepad(encode_string(s1) || ..., rate): the encoded strings,
+/// prefixed with the rate and zero padded to a whole number of blocks.
+fn absorb_bytepad(sponge: &mut KeccakSponge, strings: &[&[u8]]) {
+    let rate = sponge.rate();
+    let prefix = left_encode(rate as u64);
+    sponge.absorb(&prefix);
+
+    let mut absorbed = prefix.len();
+    for s in strings {
+        absorbed += left_encode(s.len() as u64 * 8).len() + s.len();
+        absorb_string(sponge, s);
+    }
+    sponge.absorb(&vec![0; (rate - absorbed % rate) % rate]);
+}
+
+/// cSHAKE128 and cSHAKE256, SHAKE with a function name and a customization
+/// string for domain separation.
+#[derive(Clone)]
+pub struct CShake {
+    sponge: KeccakSponge,
+    domain: u8,
+}
+
+impl CShake {
+    /// The function name is reserved for functions defined by NIST, the
+    /// customization string is free to choose. Both may be empty.
+    pub fn new_128(function_name: &[u8], customization: &[u8]) -> Self {
+        Self::with_rate(RATE_128, function_name, customization)
+    }
+
+    pub fn new_256(function_name: &[u8], customization: &[u8]) -> Self {
+        Self::with_rate(RATE_256, function_name, customization)
+    }
+
+    fn with_rate(rate: usize, function_name: &[u8], customization: &[u8]) -> Self {
+        let mut sponge = KeccakSponge::new(rate);
+
+        // without either string cSHAKE is plain SHAKE
+        if function_name.is_empty() && customization.is_empty() {
+            return Self {
+                sponge,
+                domain: SHAKE_DOMAIN,
+            };
+        }
+
+        absorb_bytepad(&mut sponge, &[function_name, customization]);
+        Self {
+            sponge,
+            domain: CSHAKE_DOMAIN,
+        }
+    }
+
+    pub fn update(&mut self, message: &[u8]) {
+        self.sponge.absorb(message);
+    }
+
+    /// Returns the first outlen bytes of the output stream.
+    pub fn finalize(self, outlen: usize) -> Vec<u8> {
+        let mut result = vec![0; outlen];
+        self.finalize_xof().read(&mut result);
+        result
+    }
+
+    /// Return a reader for the unbounded output stream.
+    pub fn finalize_xof(mut self) -> ShakeReader {
+        self.sponge.finalize(self.domain);
+        ShakeReader {
+            sponge: self.sponge,
+        }
+    }
+}
+
+/// KMAC128 and KMAC256, the Keccak based MAC. The output length is part of
+/// the hashed data, so a shorter tag is not a prefix of a longer one; the
+/// XOF variant (KMACXOF) hashes a length of zero instead.
+#[derive(Clone)]
+pub struct Kmac {
+    cshake: CShake,
+}
+
+impl Kmac {
+    /// The key should be at least 16 bytes for KMAC128 and 32 bytes for
+    /// KMAC256 to get the full security strength.
+    pub fn new_128(key: &[u8], customization: &[u8]) -> Self {
+        Self::with_rate(RATE_128, key, customization)
+    }
+
+    pub fn new_256(key: &[u8], customization: &[u8]) -> Self {
+        Self::with_rate(RATE_256, key, customization)
+    }
+
+    fn with_rate(rate: usize, key: &[u8], customization: &[u8]) -> Self {
+        let mut cshake = CShake::with_rate(rate, b"KMAC", customization);
+        absorb_bytepad(&mut cshake.sponge, &[key]);
+        Self { cshake }
+    }
+
+    pub fn update(&mut self, message: &[u8]) {
+        self.cshake.update(message);
+    }
+
+    /// Returns a tag of outlen bytes.
+    pub fn finalize(mut self, outlen: usize) -> Vec<u8> {
+        self.cshake.update(&right_encode(outlen as u64 * 8));
+        self.cshake.finalize(outlen)
+    }
+
+    /// Return a reader for the unbounded KMACXOF output stream.
+    pub fn finalize_xof(mut self) -> ShakeReader {
+        self.cshake.update(&right_encode(0));
+        self.cshake.finalize_xof()
+    }
+
+    /// Checks a tag in constant time. The tag length is the output length.
+    pub fn verify(self, tag: &[u8]) -> Result<(), MacError> {
+        if ct_eq(&self.finalize(tag.len()), tag) {
+            Ok(())
+        } else {
+            Err(MacError)
+        }
+    }
+}
+
+/// TupleHash128 and TupleHash256 hash a sequence of strings, so that
+/// ("ab", "c") and ("a", "bc") get different hashes.
+#[derive(Clone)]
+pub struct TupleHash {
+    cshake: CShake,
+}
+
+impl TupleHash {
+    pub fn new_128(customization: &[u8]) -> Self {
+        Self::with_rate(RATE_128, customization)
+    }
+
+    pub fn new_256(customization: &[u8]) -> Self {
+        Self::with_rate(RATE_256, customization)
+    }
+
+    fn with_rate(rate: usize, customization: &[u8]) -> Self {
+        Self {
+            cshake: CShake::with_rate(rate, b"TupleHash", customization),
+        }
+    }
+
+    /// Appends one whole element to the tuple. Unlike the update of a hash
+    /// function, splitting an element over two calls changes the hash.
+    pub fn push(&mut self, element: &[u8]) {
+        absorb_string(&mut self.cshake.sponge, element);
+    }
+
+    /// Returns the first outlen bytes of the hash.
+    pub fn finalize(mut self, outlen: usize) -> Vec<u8> {
+        self.cshake.update(&right_encode(outlen as u64 * 8));
+        self.cshake.finalize(outlen)
+    }
+
+    /// Return a reader for the unbounded TupleHashXOF output stream.
+    pub fn finalize_xof(mut self) -> ShakeReader {
+        self.cshake.update(&right_encode(0));
+        self.cshake.finalize_xof()
+    }
+}
+
+/// ParallelHash128 and ParallelHash256. The message is split into blocks that
+/// are hashed independently, and then the block hashes are hashed together.
+#[derive(Clone)]
+pub struct ParallelHash {
+    outer: CShake,
+    block_size: usize,
+    // the partial block not yet hashed
+    buffer: Vec<u8>,
+    blocks: u64,
+    threaded: bool,
+}
+
+impl ParallelHash {
+    /// block_size is in bytes, and is part of the hash.
+    pub fn new_128(customization: &[u8], block_size: usize) -> Self {
+        Self::with_rate(RATE_128, customization, block_size)
+    }
+
+    pub fn new_256(customization: &[u8], block_size: usize) -> Self {
+        Self::with_rate(RATE_256, customization, block_size)
+    }
+
+    fn with_rate(rate: usize, customization: &[u8], block_size: usize) -> Self {
+        if block_size == 0 {
+            panic!("ParallelHash block size has to be at least 1 byte!");
+        }
+
+        let mut outer = CShake::with_rate(rate, b"ParallelHash", customization);
+        outer.update(&left_encode(block_size as u64));
+
+        Self {
+            outer,
+            block_size,
+            buffer: Vec::with_capacity(block_size),
+            blocks: 0,
+            threaded: false,
+        }
+    }
+
+    /// Hash the blocks on separate threads. The hash does not change, but
+    /// large updates get faster.
+    pub fn threaded(mut self, threaded: bool) -> Self {
+        self.threaded = threaded;
+        self
+    }
+
+    /// The inner hash of one block, cSHAKE with empty strings (plain SHAKE)
+    /// and twice the security strength in output.
+    fn hash_block(rate: usize, block: &[u8]) -> Vec<u8> {
+        let mut inner = CShake::with_rate(rate, b"", b"");
+        inner.update(block);
+        // the capacity is twice the security strength
+        inner.finalize(200 - rate)
+    }
+
+    fn absorb_blocks(&mut self, blocks: &[u8]) {
+        let rate = self.outer.sponge.rate();
+
+        if self.threaded && blocks.len() > self.block_size {
+            let threads = thread::available_parallelism().map_or(1, |n| n.get());
+            let count = blocks.len() / self.block_size;
+            let per_thread = count.div_ceil(threads) * self.block_size;
+
+            let hashes: Vec<Vec<Vec<u8>>> = thread::scope(|s| {
+                let handles: Vec<_> = blocks
+                    .chunks(per_thread)
+                    .map(|run| {
+                        let block_size = self.block_size;
+                        s.spawn(move || {
+                            run.chunks(block_size)
+                                .map(|block| Self::hash_block(rate, block))
+                                .collect()
+                        })
+                    })
+                    .collect();
+                handles.into_iter().map(|h| h.join().unwrap()).collect()
+            });
+            for hash in hashes.iter().flatten() {
+                self.outer.update(hash);
+            }
+        } else {
+            for block in blocks.chunks(self.block_size) {
+                self.outer.update(&Self::hash_block(rate, block));
+            }
+        }
+
+        self.blocks += (blocks.len() / self.block_size) as u64;
+    }
+
+    pub fn update(&mut self, mut message: &[u8]) {
+        if !self.buffer.is_empty() {
+            let take = (self.block_size - self.buffer.len()).min(message.len());
+            self.buffer.extend_from_slice(&message[..take]);
+            message = &message[take..];
+
+            if self.buffer.len() < self.block_size {
+                return;
+            }
+            let block = std::mem::take(&mut self.buffer);
+            self.absorb_blocks(&block);
+        }
+
+        let whole = message.len() - message.len() % self.block_size;
+        self.absorb_blocks(&message[..whole]);
+        self.buffer.extend_from_slice(&message[whole..]);
+    }
+
+    /// Hashes the last partial block, then the block count and output length.
+    fn finish(&mut self, outlen_bits: u64) {
+        if !self.buffer.is_empty() {
+            let rate = self.outer.sponge.rate();
+            self.outer.update(&Self::hash_block(rate, &self.buffer));
+            self.blocks += 1;
+        }
+
+        self.outer.update(&right_encode(self.blocks));
+        self.outer.update(&right_encode(outlen_bits));
+    }
+
+    /// Returns the first outlen bytes of the hash.
+    pub fn finalize(mut self, outlen: usize) -> Vec<u8> {
+        self.finish(outlen as u64 * 8);
+        self.outer.finalize(outlen)
+    }
+
+    /// Return a reader for the unbounded ParallelHashXOF output stream.
+    pub fn finalize_xof(mut self) -> ShakeReader {
+        self.finish(0);
+        self.outer.finalize_xof()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::{CShake, Kmac, ParallelHash, TupleHash, XofReader},
+        mac::MacError,
+        slice::u8_to_hexstr,
+    };
+
+    // the inputs of the NIST SP 800-185 examples
+    const KEY: [u8; 32] = [
+        0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e,
+        0x4f, 0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x5b, 0x5c, 0x5d,
+        0x5e, 0x5f,
+    ];
+    const SHORT: [u8; 4] = [0x00, 0x01, 0x02, 0x03];
+    const PARALLEL: [u8; 24] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16,
+        0x17, 0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27,
+    ];
+
+    fn long() -> Vec<u8> {
+        (0..200).map(|i| i as u8).collect()
+    }
+
+    fn read_xof(mut reader: impl XofReader, outlen: usize) -> String {
+        let mut result = vec![0; outlen];
+        reader.read(&mut result);
+        u8_to_hexstr(&result)
+    }
+
+    #[test]
+    fn basic_cshake() {
+        let mut hasher = CShake::new_128(b"", b"Email Signature");
+        hasher.update(&SHORT);
+        let res1 = u8_to_hexstr(&hasher.finalize(32));
+        let exp1 = "c1c36925b6409a04f1b504fcbca9d82b4017277cb5ed2b2065fc1d3814d5aaf5";
+        assert_eq!(res1, exp1);
+
+        let mut hasher = CShake::new_128(b"", b"Email Signature");
+        hasher.update(&long());
+        let res2 = u8_to_hexstr(&hasher.finalize(32));
+        let exp2 = "c5221d50e4f822d96a2e8881a961420f294b7b24fe3d2094baed2c6524cc166b";
+        assert_eq!(res2, exp2);
+
+        let mut hasher = CShake::new_256(b"", b"Email Signature");
+        hasher.update(&SHORT);
+        let res3 = u8_to_hexstr(&hasher.finalize(64));
+        let exp3 = "d008828e2b80ac9d2218ffee1d070c48b8e4c87bff32c9699d5b6896eee0edd164020e2be0560858d9c00c037e34a96937c561a74c412bb4c746469527281c8c";
+        assert_eq!(res3, exp3);
+
+        let mut hasher = CShake::new_256(b"", b"Email Signature");
+        hasher.update(&long());
+        let res4 = u8_to_hexstr(&hasher.finalize(64));
+        let exp4 = "07dc27b11e51fbac75bc7b3c1d983e8b4b85fb1defaf218912ac86430273091727f42b17ed1df63e8ec118f04b23633c1dfb1574c8fb55cb45da8e25afb092bb";
+        assert_eq!(res4, exp4);
+    }
+
+    #[test]
+    fn cshake_without_strings_is_shake() {
+        let mut hasher = CShake::new_128(b"", b"");
+        hasher.update(b"abc");
+        let res1 = u8_to_hexstr(&hasher.finalize(32));
+        let exp1 = "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc8";
+        assert_eq!(res1, exp1);
+    }
+
+    #[test]
+    fn basic_kmac() {
+        let mut mac = Kmac::new_128(&KEY, b"");
+        mac.update(&SHORT);
+        let res1 = u8_to_hexstr(&mac.finalize(32));
+        let exp1 = "e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e";
+        assert_eq!(res1, exp1);
+
+        let mut mac = Kmac::new_128(&KEY, b"My Tagged Application");
+        mac.update(&SHORT);
+        let res2 = u8_to_hexstr(&mac.finalize(32));
+        let exp2 = "3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5";
+        assert_eq!(res2, exp2);
+
+        let mut mac = Kmac::new_128(&KEY, b"My Tagged Application");
+        mac.update(&long());
+        let res3 = u8_to_hexstr(&mac.finalize(32));
+        let exp3 = "1f5b4e6cca02209e0dcb5ca635b89a15e271ecc760071dfd805faa38f9729230";
+        assert_eq!(res3, exp3);
+
+        let mut mac = Kmac::new_256(&KEY, b"My Tagged Application");
+        mac.update(&SHORT);
+        let res4 = u8_to_hexstr(&mac.finalize(64));
+        let exp4 = "20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d273a68d2f7f69d4cc3de9d104a351689f27cf6f5951f0103f33f4f24871024d9c27773a8dd";
+        assert_eq!(res4, exp4);
+
+        let mut mac = Kmac::new_256(&KEY, b"");
+        mac.update(&long());
+        let res5 = u8_to_hexstr(&mac.finalize(64));
+        let exp5 = "75358cf39e41494e949707927cee0af20a3ff553904c86b08f21cc414bcfd691589d27cf5e15369cbbff8b9a4c2eb17800855d0235ff635da82533ec6b759b69";
+        assert_eq!(res5, exp5);
+    }
+
+    #[test]
+    fn kmac_xof() {
+        let mut mac = Kmac::new_128(&KEY, b"My Tagged Application");
+        mac.update(&long());
+        let res1 = read_xof(mac.finalize_xof(), 32);
+        let exp1 = "47026c7cd793084aa0283c253ef658490c0db61438b8326fe9bddf281b83ae0f";
+        assert_eq!(res1, exp1);
+
+        let mut mac = Kmac::new_256(&KEY, b"My Tagged Application");
+        mac.update(&SHORT);
+        let res2 = read_xof(mac.finalize_xof(), 64);
+        let exp2 = "1755133f1534752aad0748f2c706fb5c784512cab835cd15676b16c0c6647fa96faa7af634a0bf8ff6df39374fa00fad9a39e322a7c92065a64eb1fb0801eb2b";
+        assert_eq!(res2, exp2);
+    }
+
+    #[test]
+    fn kmac_verify() {
+        let mut mac = Kmac::new_128(&KEY, b"");
+        mac.update(&SHORT);
+        let mut tag = mac.clone().finalize(32);
+        assert_eq!(mac.clone().verify(&tag), Ok(()));
+
+        // a truncated tag was computed for a different length
+        assert_eq!(mac.clone().verify(&tag[..16]), Err(MacError));
+
+        tag[31] ^= 1;
+        assert_eq!(mac.verify(&tag), Err(MacError));
+    }
+
+    #[test]
+    fn basic_tuple_hash() {
+        let te3: &[u8] = &[0x00, 0x01, 0x02];
+        let te6: &[u8] = &[0x10, 0x11, 0x12, 0x13, 0x14, 0x15];
+        let te9: &[u8] = &[0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28];
+
+        let mut hasher = TupleHash::new_128(b"");
+        hasher.push(te3);
+        hasher.push(te6);
+        let res1 = u8_to_hexstr(&hasher.finalize(32));
+        let exp1 = "c5d8786c1afb9b82111ab34b65b2c0048fa64e6d48e263264ce1707d3ffc8ed1";
+        assert_eq!(res1, exp1);
+
+        let mut hasher = TupleHash::new_128(b"My Tuple App");
+        hasher.push(te3);
+        hasher.push(te6);
+        hasher.push(te9);
+        let res2 = u8_to_hexstr(&hasher.finalize(32));
+        let exp2 = "e60f202c89a2631eda8d4c588ca5fd07f39e5151998deccf973adb3804bb6e84";
+        assert_eq!(res2, exp2);
+
+        let mut hasher = TupleHash::new_256(b"My Tuple App");
+        hasher.push(te3);
+        hasher.push(te6);
+        let res3 = u8_to_hexstr(&hasher.finalize(64));
+        let exp3 = "147c2191d5ed7efd98dbd96d7ab5a11692576f5fe2a5065f3e33de6bba9f3aa1c4e9a068a289c61c95aab30aee1e410b0b607de3620e24a4e3bf9852a1d4367e";
+        assert_eq!(res3, exp3);
+
+        let mut hasher = TupleHash::new_128(b"My Tuple App");
+        hasher.push(te3);
+        hasher.push(te6);
+        let res4 = read_xof(hasher.finalize_xof(), 32);
+        let exp4 = "3fc8ad69453128292859a18b6c67d7ad85f01b32815e22ce839c49ec374e9b9a";
+        assert_eq!(res4, exp4);
+    }
+
+    #[test]
+    fn tuple_hash_is_unambiguous() {
+        let mut hasher = TupleHash::new_128(b"");
+        hasher.push(b"ab");
+        hasher.push(b"c");
+        let res1 = hasher.finalize(32);
+
+        let mut hasher = TupleHash::new_128(b"");
+        hasher.push(b"a");
+        hasher.push(b"bc");
+        let res2 = hasher.finalize(32);
+
+        assert_ne!(res1, res2);
+    }
+
+    #[test]
+    fn basic_parallel_hash() {
+        let mut hasher = ParallelHash::new_128(b"", 8);
+        hasher.update(&PARALLEL);
+        let res1 = u8_to_hexstr(&hasher.finalize(32));
+        let exp1 = "ba8dc1d1d979331d3f813603c67f72609ab5e44b94a0b8f9af46514454a2b4f5";
+        assert_eq!(res1, exp1);
+
+        let mut hasher = ParallelHash::new_128(b"Parallel Data", 8);
+        hasher.update(&PARALLEL);
+        let res2 = u8_to_hexstr(&hasher.finalize(32));
+        let exp2 = "fc484dcb3f84dceedc353438151bee58157d6efed0445a81f165e495795b7206";
+        assert_eq!(res2, exp2);
+
+        // split in the middle of a block
+        let mut hasher = ParallelHash::new_256(b"", 8);
+        hasher.update(&PARALLEL[..13]);
+        hasher.update(&PARALLEL[13..]);
+        let res3 = u8_to_hexstr(&hasher.finalize(64));
+        let exp3 = "bc1ef124da34495e948ead207dd9842235da432d2bbc54b4c110e64c451105531b7f2a3e0ce055c02805e7c2de1fb746af97a1dd01f43b824e31b87612410429";
+        assert_eq!(res3, exp3);
+
+        let mut hasher = ParallelHash::new_256(b"Parallel Data", 8);
+        hasher.update(&PARALLEL);
+        let res4 = u8_to_hexstr(&hasher.finalize(64));
+        let exp4 = "cdf15289b54f6212b4bc270528b49526006dd9b54e2b6add1ef6900dda3963bb33a72491f236969ca8afaea29c682d47a393c065b38e29fae651a2091c833110";
+        assert_eq!(res4, exp4);
+    }
+
+    #[test]
+    fn parallel_hash_threaded() {
+        // 1000 bytes is 15 whole blocks of 64 and a partial one
+        let message: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+        let exp1 = "8a585aa594967123ebcdc3252a86cc8837f83ef46947982672bad411ca47a0cd";
+        let exp2 = "b2714a892353d6d23cc91d4552efdb0c88968f8237fd5777056d4be7c5fa8852eb79f71028baa691d150464018b6650006d6f8ba126aa608721ebc9fdfa3b649";
+
+        for threaded in [false, true] {
+            let mut hasher = ParallelHash::new_128(b"Parallel Data", 64).threaded(threaded);
+            hasher.update(&message[..100]);
+            hasher.update(&message[100..]);
+            assert_eq!(u8_to_hexstr(&hasher.finalize(32)), exp1);
+
+            let mut hasher = ParallelHash::new_256(b"", 64).threaded(threaded);
+            hasher.update(&message);
+            assert_eq!(read_xof(hasher.finalize_xof(), 64), exp2);
+        }
+    }
+}
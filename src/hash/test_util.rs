@@ -0,0 +1,14 @@
+use crate::{hash::HashFunction, slice::u8_to_hexstr};
+
+/// Checks the message is hashed the same way in one go or byte by byte.
+pub(crate) fn check<H: HashFunction>(new: fn() -> H, message: &[u8], expected: &str) {
+    let mut hasher = new();
+    hasher.update(message);
+    assert_eq!(u8_to_hexstr(&hasher.digest()), expected);
+
+    let mut hasher = new();
+    for byte in message {
+        hasher.update(&[*byte]);
+    }
+    assert_eq!(u8_to_hexstr(&hasher.digest()), expected);
+}
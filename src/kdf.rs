@@ -0,0 +1,11 @@
+mod argon2;
+mod bcrypt;
+mod pbkdf2;
+mod phc;
+mod scrypt;
+
+pub use argon2::{Argon2, Argon2Error, Argon2Params, Argon2Variant};
+pub use bcrypt::{BcryptError, BcryptHash, BcryptVersion, bcrypt_verify};
+pub use pbkdf2::{pbkdf2, try_pbkdf2};
+pub use phc::{PhcError, PhcLimits, PhcString, verify_password, verify_password_with_limits};
+pub use scrypt::{Scrypt, ScryptError};
@@ -0,0 +1,663 @@
+use std::{fmt, mem, thread};
+
+use super::phc::{PhcError, PhcLimits, PhcString, b64_decode, b64_encode};
+use crate::{
+    hash::{Blake2b, HashFunction},
+    slice::ct_eq,
+};
+
+const ARGON2_VERSION: u32 = 0x13;
+
+// every pass over a lane is split into four slices, the lanes synchronize
+// after each of them
+const SYNC_POINTS: usize = 4;
+
+const BLOCK_WORDS: usize = 128;
+const BLOCK_BYTES: usize = BLOCK_WORDS * 8;
+
+type Block = [u64; BLOCK_WORDS];
+
+/// Why Argon2 refused to hash a password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Argon2Error {
+    /// the salt is shorter than the 8 bytes the RFC asks for
+    SaltTooShort { length: usize },
+}
+
+impl fmt::Display for Argon2Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SaltTooShort { length } => {
+                write!(f, "Argon2 salt too short: {length} bytes, at least 8")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Argon2Error {}
+
+/// The three flavours of Argon2. Argon2id is the recommended one, Argon2d is
+/// faster but its memory access pattern depends on the password.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Argon2Variant {
+    Argon2d,
+    Argon2i,
+    Argon2id,
+}
+
+impl Argon2Variant {
+    /// y in the RFC, mixed into H0 and the address blocks.
+    fn id(self) -> u32 {
+        match self {
+            Self::Argon2d => 0,
+            Self::Argon2i => 1,
+            Self::Argon2id => 2,
+        }
+    }
+
+    /// The algorithm identifier in PHC strings.
+    fn phc_name(self) -> &'static str {
+        match self {
+            Self::Argon2d => "argon2d",
+            Self::Argon2i => "argon2i",
+            Self::Argon2id => "argon2id",
+        }
+    }
+}
+
+/// The Argon2 parameters (RFC 9106 section 3.1). The defaults are the second
+/// recommended option of the RFC: 64 MiB of memory, 3 passes and 4 lanes.
+#[derive(Clone)]
+pub struct Argon2Params {
+    variant: Argon2Variant,
+    memory: u32,
+    iterations: u32,
+    lanes: u32,
+    tag_length: u32,
+    secret: Vec<u8>,
+    associated_data: Vec<u8>,
+    threaded: bool,
+}
+
+impl Argon2Params {
+    pub fn new(variant: Argon2Variant) -> Self {
+        Self {
+            variant,
+            memory: 64 * 1024,
+            iterations: 3,
+            lanes: 4,
+            tag_length: 32,
+            secret: Vec::new(),
+            associated_data: Vec::new(),
+            threaded: false,
+        }
+    }
+
+    /// Memory size in KiB, at least 8 per lane. It is rounded down to a
+    /// multiple of 4 * lanes.
+    pub fn memory(mut self, memory: u32) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// Number of passes over the memory, at least 1.
+    pub fn iterations(mut self, iterations: u32) -> Self {
+        if iterations == 0 {
+            panic!("Argon2 needs at least one iteration!");
+        }
+        self.iterations = iterations;
+        self
+    }
+
+    /// Degree of parallelism, from 1 to 2^24 - 1. Changes the hash.
+    pub fn lanes(mut self, lanes: u32) -> Self {
+        if lanes == 0 || lanes >= 1 << 24 {
+            panic!("Argon2 lane count not supported! {lanes} (supported: 1 to 2^24 - 1)");
+        }
+        self.lanes = lanes;
+        self
+    }
+
+    /// Length of the produced tag in bytes, at least 4.
+    pub fn tag_length(mut self, tag_length: u32) -> Self {
+        if tag_length < 4 {
+            panic!("Argon2 tag length too short! {tag_length} < 4");
+        }
+        self.tag_length = tag_length;
+        self
+    }
+
+    /// Secret value (a pepper) kept apart from the stored hashes.
+    pub fn secret(mut self, secret: &[u8]) -> Self {
+        self.secret = secret.to_vec();
+        self
+    }
+
+    /// Associated data that is bound to the tag.
+    pub fn associated_data(mut self, associated_data: &[u8]) -> Self {
+        self.associated_data = associated_data.to_vec();
+        self
+    }
+
+    /// Fill the lanes on separate threads, at most one per available core.
+    /// The tag does not change.
+    pub fn threaded(mut self, threaded: bool) -> Self {
+        self.threaded = threaded;
+        self
+    }
+
+    pub fn build(self) -> Argon2 {
+        if self.memory < 8 * self.lanes {
+            panic!(
+                "Argon2 memory too small! {} KiB < 8 KiB * {} lanes",
+                self.memory, self.lanes
+            );
+        }
+        Argon2 { params: self }
+    }
+}
+
+/// Argon2 password hashing (RFC 9106), the winner of the Password Hashing
+/// Competition. Memory is filled with blocks that depend on earlier blocks,
+/// so computing the tag with less memory costs a lot more time.
+#[derive(Clone)]
+pub struct Argon2 {
+    params: Argon2Params,
+}
+
+/// H' from the RFC, BLAKE2b with outputs longer than 64 bytes: chained
+/// hashes that contribute 32 bytes each, and the last one the rest.
+fn blake2b_long(input: &[&[u8]], out: &mut [u8]) {
+    let mut hasher = Blake2b::new(out.len().min(64));
+    hasher.update(&(out.len() as u32).to_le_bytes());
+    for part in input {
+        hasher.update(part);
+    }
+
+    if out.len() <= 64 {
+        out.copy_from_slice(&hasher.digest());
+        return;
+    }
+
+    let mut v = hasher.digest();
+    out[..32].copy_from_slice(&v[..32]);
+    let mut pos = 32;
+    while out.len() - pos > 64 {
+        v = Blake2b::hash(&v);
+        out[pos..pos + 32].copy_from_slice(&v[..32]);
+        pos += 32;
+    }
+    let mut hasher = Blake2b::new(out.len() - pos);
+    hasher.update(&v);
+    out[pos..].copy_from_slice(&hasher.digest());
+}
+
+fn bytes_to_block(bytes: &[u8]) -> Block {
+    let mut block = [0; BLOCK_WORDS];
+    for (word, chunk) in block.iter_mut().zip(bytes.chunks_exact(8)) {
+        *word = u64::from_le_bytes(chunk.try_into().expect("len 8"));
+    }
+    block
+}
+
+/// The BlaMka multiplication, a + b with an extra 2 * lo(a) * lo(b) to mix
+/// the bits faster than plain addition.
+fn blamka(a: u64, b: u64) -> u64 {
+    let product = (a & 0xffffffff) * (b & 0xffffffff);
+    a.wrapping_add(b).wrapping_add(product.wrapping_mul(2))
+}
+
+/// GB, the BLAKE2b G function with BlaMka instead of additions and without
+/// message words.
+fn mix(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize) {
+    v[a] = blamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = blamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = blamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = blamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// P, one BLAKE2b round over eight 128-bit registers.
+fn permute(v: &mut [u64; 16]) {
+    mix(v, 0, 4, 8, 12);
+    mix(v, 1, 5, 9, 13);
+    mix(v, 2, 6, 10, 14);
+    mix(v, 3, 7, 11, 15);
+    mix(v, 0, 5, 10, 15);
+    mix(v, 1, 6, 11, 12);
+    mix(v, 2, 7, 8, 13);
+    mix(v, 3, 4, 9, 14);
+}
+
+/// The compression function G(X, Y): P over the rows, then over the columns
+/// of the 8x8 matrix of registers in X ^ Y, and the input added back.
+fn compress(x: &Block, y: &Block) -> Block {
+    let mut r = [0; BLOCK_WORDS];
+    for i in 0..BLOCK_WORDS {
+        r[i] = x[i] ^ y[i];
+    }
+
+    let mut z = r;
+    for row in z.chunks_exact_mut(16) {
+        let mut v: [u64; 16] = row.try_into().expect("len 16");
+        permute(&mut v);
+        row.copy_from_slice(&v);
+    }
+    for column in 0..8 {
+        let mut v = [0; 16];
+        for j in 0..8 {
+            v[2 * j] = z[2 * column + 16 * j];
+            v[2 * j + 1] = z[2 * column + 16 * j + 1];
+        }
+        permute(&mut v);
+        for j in 0..8 {
+            z[2 * column + 16 * j] = v[2 * j];
+            z[2 * column + 16 * j + 1] = v[2 * j + 1];
+        }
+    }
+
+    for i in 0..BLOCK_WORDS {
+        z[i] ^= r[i];
+    }
+    z
+}
+
+impl Argon2 {
+    /// Argon2 with the default parameters of the given variant.
+    pub fn new(variant: Argon2Variant) -> Self {
+        Argon2Params::new(variant).build()
+    }
+
+    /// The tag of password with salt, the salt has to be at least 8 bytes.
+    pub fn hash(&self, password: &[u8], salt: &[u8]) -> Result<Vec<u8>, Argon2Error> {
+        if salt.len() < 8 {
+            return Err(Argon2Error::SaltTooShort { length: salt.len() });
+        }
+        let p = &self.params;
+        let lanes = p.lanes as usize;
+
+        let mut h0 = Blake2b::new(64);
+        for value in [
+            p.lanes,
+            p.tag_length,
+            p.memory,
+            p.iterations,
+            ARGON2_VERSION,
+            p.variant.id(),
+        ] {
+            h0.update(&value.to_le_bytes());
+        }
+        for field in [password, salt, &p.secret, &p.associated_data] {
+            h0.update(&(field.len() as u32).to_le_bytes());
+            h0.update(field);
+        }
+        let h0 = h0.digest();
+
+        // the memory is held as segments, lane after lane, so the segments
+        // that are being filled can be taken out while the rest is shared
+        let segment_length = p.memory as usize / (SYNC_POINTS * lanes);
+        let mut memory = vec![vec![[0; BLOCK_WORDS]; segment_length]; lanes * SYNC_POINTS];
+        for lane in 0..lanes {
+            for i in 0..2 {
+                let mut bytes = [0; BLOCK_BYTES];
+                let index = (i as u32).to_le_bytes();
+                let lane_bytes = (lane as u32).to_le_bytes();
+                blake2b_long(&[&h0, &index, &lane_bytes], &mut bytes);
+                memory[lane * SYNC_POINTS][i] = bytes_to_block(&bytes);
+            }
+        }
+
+        let workers = thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(lanes);
+        for pass in 0..p.iterations as usize {
+            for slice in 0..SYNC_POINTS {
+                let mut current: Vec<Vec<Block>> = (0..lanes)
+                    .map(|lane| mem::take(&mut memory[lane * SYNC_POINTS + slice]))
+                    .collect();
+
+                if p.threaded && lanes > 1 {
+                    // every worker takes a run of neighbouring lanes
+                    let lanes_per_worker = lanes.div_ceil(workers);
+                    thread::scope(|s| {
+                        for (i, chunk) in current.chunks_mut(lanes_per_worker).enumerate() {
+                            let memory = &memory;
+                            s.spawn(move || {
+                                for (j, segment) in chunk.iter_mut().enumerate() {
+                                    let lane = i * lanes_per_worker + j;
+                                    self.fill_segment(memory, segment, pass, lane, slice);
+                                }
+                            });
+                        }
+                    });
+                } else {
+                    for (lane, segment) in current.iter_mut().enumerate() {
+                        self.fill_segment(&memory, segment, pass, lane, slice);
+                    }
+                }
+
+                for (lane, segment) in current.into_iter().enumerate() {
+                    memory[lane * SYNC_POINTS + slice] = segment;
+                }
+            }
+        }
+
+        // C, the xor of the last block of every lane
+        let mut last = [0; BLOCK_WORDS];
+        for lane in 0..lanes {
+            let block = &memory[lane * SYNC_POINTS + SYNC_POINTS - 1][segment_length - 1];
+            for i in 0..BLOCK_WORDS {
+                last[i] ^= block[i];
+            }
+        }
+        let mut bytes = Vec::with_capacity(BLOCK_BYTES);
+        for word in last {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+
+        let mut tag = vec![0; p.tag_length as usize];
+        blake2b_long(&[&bytes], &mut tag);
+        Ok(tag)
+    }
+
+    /// The tag as a PHC string, e.g. $argon2id$v=19$m=65536,t=3,p=4$salt$tag.
+    /// The associated data is stored in the data parameter, the secret is
+    /// not stored.
+    pub fn hash_phc(&self, password: &[u8], salt: &[u8]) -> Result<String, Argon2Error> {
+        let p = &self.params;
+        let mut params = vec![
+            ("m".to_string(), p.memory.to_string()),
+            ("t".to_string(), p.iterations.to_string()),
+            ("p".to_string(), p.lanes.to_string()),
+        ];
+        if !p.associated_data.is_empty() {
+            params.push(("data".to_string(), b64_encode(&p.associated_data)));
+        }
+
+        Ok(PhcString {
+            algorithm: p.variant.phc_name().to_string(),
+            version: Some(ARGON2_VERSION),
+            params,
+            salt: Some(salt.to_vec()),
+            hash: Some(self.hash(password, salt)?),
+        }
+        .to_string())
+    }
+
+    /// Computes the blocks of one segment. memory holds every other segment,
+    /// the ones of the same slice in other lanes are never referenced.
+    fn fill_segment(
+        &self,
+        memory: &[Vec<Block>],
+        segment: &mut [Block],
+        pass: usize,
+        lane: usize,
+        slice: usize,
+    ) {
+        let p = &self.params;
+        let lanes = p.lanes as usize;
+        let segment_length = segment.len();
+        let lane_length = segment_length * SYNC_POINTS;
+        let block_at = |segment: &[Block], ref_lane: usize, column: usize| -> Block {
+            let ref_slice = column / segment_length;
+            if ref_lane == lane && ref_slice == slice {
+                segment[column % segment_length]
+            } else {
+                memory[ref_lane * SYNC_POINTS + ref_slice][column % segment_length]
+            }
+        };
+
+        // Argon2i and the first half of the first pass of Argon2id take the
+        // reference blocks from a counter instead of the memory
+        let data_independent = match p.variant {
+            Argon2Variant::Argon2d => false,
+            Argon2Variant::Argon2i => true,
+            Argon2Variant::Argon2id => pass == 0 && slice < SYNC_POINTS / 2,
+        };
+        let mut input = [0; BLOCK_WORDS];
+        input[..6].copy_from_slice(&[
+            pass as u64,
+            lane as u64,
+            slice as u64,
+            (lane_length * lanes) as u64,
+            p.iterations as u64,
+            p.variant.id() as u64,
+        ]);
+        let mut addresses = [0; BLOCK_WORDS];
+        let mut next_addresses = |addresses: &mut Block| {
+            input[6] += 1;
+            let zero = [0; BLOCK_WORDS];
+            *addresses = compress(&zero, &compress(&zero, &input));
+        };
+
+        // the first two blocks of a lane come from H0
+        let start = if pass == 0 && slice == 0 { 2 } else { 0 };
+        if data_independent && start != 0 {
+            next_addresses(&mut addresses);
+        }
+
+        for index in start..segment_length {
+            let column = slice * segment_length + index;
+            if data_independent && index % BLOCK_WORDS == 0 {
+                next_addresses(&mut addresses);
+            }
+
+            let prev = block_at(segment, lane, (column + lane_length - 1) % lane_length);
+            let pseudo_random = if data_independent {
+                addresses[index % BLOCK_WORDS]
+            } else {
+                prev[0]
+            };
+            let j1 = pseudo_random & 0xffffffff;
+            let j2 = pseudo_random >> 32;
+
+            let ref_lane = if pass == 0 && slice == 0 {
+                lane
+            } else {
+                (j2 % lanes as u64) as usize
+            };
+
+            // the reference set: the finished blocks of the lane, or the
+            // finished slices of another lane, minus the previous block
+            let area = if pass == 0 {
+                if ref_lane == lane {
+                    column - 1
+                } else {
+                    slice * segment_length - (index == 0) as usize
+                }
+            } else if ref_lane == lane {
+                lane_length - segment_length + index - 1
+            } else {
+                lane_length - segment_length - (index == 0) as usize
+            };
+
+            // map j1 onto the area, with a bias towards recent blocks
+            let x = (j1 * j1) >> 32;
+            let relative = area - 1 - ((area as u64 * x) >> 32) as usize;
+            let area_start = if pass == 0 || slice == SYNC_POINTS - 1 {
+                0
+            } else {
+                (slice + 1) * segment_length
+            };
+            let ref_column = (area_start + relative) % lane_length;
+
+            let reference = block_at(segment, ref_lane, ref_column);
+            let new = compress(&prev, &reference);
+            if pass == 0 {
+                segment[index] = new;
+            } else {
+                // version 0x13 xors over the block of the previous pass
+                for i in 0..BLOCK_WORDS {
+                    segment[index][i] ^= new[i];
+                }
+            }
+        }
+    }
+}
+
+/// Reruns Argon2 with the parameters of a PHC string from hash_phc().
+pub(super) fn verify_phc(
+    phc: &PhcString,
+    password: &[u8],
+    secret: &[u8],
+    limits: &PhcLimits,
+) -> Result<bool, PhcError> {
+    let invalid = |name: &str| PhcError::InvalidParam(name.to_string());
+
+    let variant = [
+        Argon2Variant::Argon2d,
+        Argon2Variant::Argon2i,
+        Argon2Variant::Argon2id,
+    ]
+    .into_iter()
+    .find(|v| v.phc_name() == phc.algorithm)
+    .ok_or_else(|| PhcError::UnknownAlgorithm(phc.algorithm.clone()))?;
+    if phc.version != Some(ARGON2_VERSION) {
+        return Err(PhcError::UnsupportedVersion);
+    }
+
+    let mut params = Argon2Params::new(variant).secret(secret);
+    let (mut memory, mut iterations, mut lanes) = (None, None, None);
+    for (name, value) in &phc.params {
+        // plain decimal, u32::from_str would also take a leading +
+        let number = || {
+            if !value.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid(name));
+            }
+            value.parse::<u32>().map_err(|_| invalid(name))
+        };
+        match name.as_str() {
+            "m" => memory = Some(number()?),
+            "t" => iterations = Some(number()?),
+            "p" => lanes = Some(number()?),
+            "data" => params = params.associated_data(&b64_decode(value)?),
+            _ => return Err(invalid(name)),
+        }
+    }
+
+    let iterations = iterations
+        .filter(|&t| t > 0 && limits.check_iterations(t))
+        .ok_or_else(|| invalid("t"))?;
+    let lanes = lanes
+        .filter(|&p| p > 0 && p < 1 << 24 && limits.check_lanes(p))
+        .ok_or_else(|| invalid("p"))?;
+    let memory = memory
+        .filter(|&m| m >= 8 * lanes && limits.check_memory(m as u64 * 1024))
+        .ok_or_else(|| invalid("m"))?;
+    let salt = phc
+        .salt
+        .as_deref()
+        .filter(|s| s.len() >= 8)
+        .ok_or_else(|| invalid("salt"))?;
+    let expected = phc
+        .hash
+        .as_deref()
+        .filter(|h| h.len() >= 4)
+        .ok_or_else(|| invalid("hash"))?;
+
+    let argon2 = params
+        .memory(memory)
+        .iterations(iterations)
+        .lanes(lanes)
+        .tag_length(expected.len() as u32)
+        .build();
+    let tag = argon2.hash(password, salt).map_err(|_| invalid("salt"))?;
+    Ok(ct_eq(&tag, expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        kdf::{Argon2, Argon2Error, Argon2Params, Argon2Variant},
+        slice::u8_to_hexstr,
+    };
+
+    #[test]
+    fn argon2_rfc9106() {
+        let cases = [
+            (
+                Argon2Variant::Argon2d,
+                "512b391b6f1162975371d30919734294f868e3be3984f3c1a13a4db9fabe4acb",
+            ),
+            (
+                Argon2Variant::Argon2i,
+                "c814d9d1dc7f37aa13f0d77f2494bda1c8de6b016dd388d29952a4c4672b6ce8",
+            ),
+            (
+                Argon2Variant::Argon2id,
+                "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659",
+            ),
+        ];
+
+        for (variant, expected) in cases {
+            for threaded in [false, true] {
+                let argon2 = Argon2Params::new(variant)
+                    .memory(32)
+                    .iterations(3)
+                    .lanes(4)
+                    .tag_length(32)
+                    .secret(&[3; 8])
+                    .associated_data(&[4; 12])
+                    .threaded(threaded)
+                    .build();
+                let res1 = u8_to_hexstr(&argon2.hash(&[1; 32], &[2; 16]).unwrap());
+                assert_eq!(res1, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn argon2_one_lane() {
+        // long enough for more than one address block per segment
+        let cases = [
+            (
+                Argon2Variant::Argon2d,
+                "25c4ee8ba448054b49efc804e478b9d823be1f9bd2e99f51d6ec4007a3a1501f",
+            ),
+            (
+                Argon2Variant::Argon2i,
+                "89e9029f4637b295beb027056a7336c414fadd43f6b208645281cb214a56452f",
+            ),
+            (
+                Argon2Variant::Argon2id,
+                "9dfeb910e80bad0311fee20f9c0e2b12c17987b4cac90c2ef54d5b3021c68bfe",
+            ),
+        ];
+
+        for (variant, expected) in cases {
+            let argon2 = Argon2Params::new(variant)
+                .memory(256)
+                .iterations(2)
+                .lanes(1)
+                .build();
+            let res1 = u8_to_hexstr(&argon2.hash(b"password", b"somesalt").unwrap());
+            assert_eq!(res1, expected);
+        }
+    }
+
+    #[test]
+    fn argon2_long_tag() {
+        // 100 KiB is rounded down to 96 for 3 lanes, and the tag needs the
+        // chained form of H'
+        let argon2 = Argon2Params::new(Argon2Variant::Argon2id)
+            .memory(100)
+            .iterations(2)
+            .lanes(3)
+            .tag_length(100)
+            .build();
+        let res1 = u8_to_hexstr(&argon2.hash(b"password", b"somesalt").unwrap());
+        let exp1 = "d98ff35be4929d949cd4de3dcf10482d8a006a5809bae1c331feae87936a1f4ea0434327ff12c78557fc368fc6c7ac185f8ae722254afc7754cd6c1bf25170301463e34fd1495bd02cf900c3df6aecfda17b00658ff0b59f8726eac34a52d1b62d96ab74";
+        assert_eq!(res1, exp1);
+    }
+
+    #[test]
+    fn argon2_short_salt() {
+        let argon2 = Argon2::new(Argon2Variant::Argon2id);
+        let error = Argon2Error::SaltTooShort { length: 4 };
+        assert_eq!(argon2.hash(b"password", b"salt"), Err(error.clone()));
+        assert_eq!(argon2.hash_phc(b"password", b"salt"), Err(error));
+    }
+}
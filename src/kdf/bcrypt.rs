@@ -0,0 +1,391 @@
+use std::fmt;
+
+use rand::CryptoRng;
+
+use self::blowfish::Blowfish;
+use crate::slice::ct_eq;
+
+mod blowfish;
+
+// bcrypt's own base64 alphabet, in a different order than the standard one
+const BCRYPT_ALPHABET: &[u8; 64] =
+    b"./ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+// longer passwords are cut off, the terminating NUL byte included
+const BCRYPT_MAX_KEY_BYTES: usize = 72;
+
+const BCRYPT_MIN_COST: u32 = 4;
+const BCRYPT_MAX_COST: u32 = 31;
+
+const BCRYPT_SALT_BYTES: usize = 16;
+// the last byte of the 24 byte ciphertext is left out of the hash
+const BCRYPT_HASH_BYTES: usize = 23;
+
+// "OrpheanBeholderScryDoubt"
+const BCRYPT_CTEXT: [u32; 6] = [
+    0x4f727068, 0x65616e42, 0x65686f6c, 0x64657253, 0x63727944, 0x6f756274,
+];
+
+/// The prefix of a bcrypt hash. All three are computed the same way here:
+/// $2a$ had a length overflow in OpenBSD for passwords longer than 255 bytes
+/// (which get cut to 72 anyway), and $2y$ is crypt_blowfish's name for a
+/// correct $2a$. The broken $2x$ hashes are not supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BcryptVersion {
+    V2a,
+    V2b,
+    V2y,
+}
+
+impl BcryptVersion {
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::V2a => "2a",
+            Self::V2b => "2b",
+            Self::V2y => "2y",
+        }
+    }
+}
+
+/// Why a bcrypt hash string was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BcryptError {
+    /// not a $2b$cost$saltandhash string of 60 characters
+    Format,
+    /// a prefix other than $2a$, $2b$ or $2y$
+    UnsupportedVersion,
+    /// a cost outside of 4 to 31
+    Cost(u32),
+}
+
+impl fmt::Display for BcryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Format => write!(f, "malformed bcrypt hash"),
+            Self::UnsupportedVersion => write!(f, "unsupported bcrypt version"),
+            Self::Cost(cost) => write!(f, "bcrypt cost {cost} out of range (4 to 31)"),
+        }
+    }
+}
+
+impl std::error::Error for BcryptError {}
+
+/// A bcrypt password hash, as stored in the $2b$12$<salt><hash> format.
+/// The fields are only set by new() and parse(), which check the cost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BcryptHash {
+    version: BcryptVersion,
+    cost: u32,
+    salt: [u8; BCRYPT_SALT_BYTES],
+    hash: [u8; BCRYPT_HASH_BYTES],
+}
+
+/// EksBlowfishSetup with the password as the key, then encrypt the magic
+/// text 64 times.
+fn bcrypt(password: &[u8], cost: u32, salt: &[u8; BCRYPT_SALT_BYTES]) -> [u8; BCRYPT_HASH_BYTES] {
+    let mut key = password.to_vec();
+    key.push(0);
+    key.truncate(BCRYPT_MAX_KEY_BYTES);
+
+    let mut cipher = Blowfish::initial();
+    cipher.expand_key(&key, salt);
+    for _ in 0..1u64 << cost {
+        cipher.expand_key(&key, &[0; 16]);
+        cipher.expand_key(salt, &[0; 16]);
+    }
+
+    let mut ctext = BCRYPT_CTEXT;
+    for _ in 0..64 {
+        for block in ctext.chunks_exact_mut(2) {
+            (block[0], block[1]) = cipher.encrypt(block[0], block[1]);
+        }
+    }
+
+    let mut result = [0; BCRYPT_HASH_BYTES];
+    for (bytes, word) in result.chunks_mut(4).zip(ctext) {
+        bytes.copy_from_slice(&word.to_be_bytes()[..bytes.len()]);
+    }
+    result
+}
+
+fn bcrypt_b64_encode(data: &[u8]) -> String {
+    let mut result = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let mut group = [0u8; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+        for i in 0..=chunk.len() {
+            let index = (bits >> (18 - 6 * i)) & 0x3f;
+            result.push(BCRYPT_ALPHABET[index as usize] as char);
+        }
+    }
+    result
+}
+
+/// Fills out from s. The unused low bits of the last character are ignored,
+/// like other implementations do.
+fn bcrypt_b64_decode(s: &[u8], out: &mut [u8]) -> Result<(), BcryptError> {
+    let mut bits = 0u32;
+    let mut count = 0;
+    let mut written = 0;
+    for c in s {
+        let value = BCRYPT_ALPHABET
+            .iter()
+            .position(|a| a == c)
+            .ok_or(BcryptError::Format)?;
+        bits = (bits << 6) | value as u32;
+        count += 6;
+        if count >= 8 && written < out.len() {
+            count -= 8;
+            out[written] = (bits >> count) as u8;
+            written += 1;
+        }
+    }
+
+    if written != out.len() {
+        return Err(BcryptError::Format);
+    }
+    Ok(())
+}
+
+impl BcryptHash {
+    /// Hashes password with the given cost (4 to 31) and salt, as $2b$.
+    /// Only the first 72 bytes of the password count.
+    pub fn new(password: &[u8], cost: u32, salt: [u8; BCRYPT_SALT_BYTES]) -> Self {
+        if !(BCRYPT_MIN_COST..=BCRYPT_MAX_COST).contains(&cost) {
+            panic!("bcrypt cost not supported! {cost} (supported: 4 to 31)");
+        }
+
+        Self {
+            version: BcryptVersion::V2b,
+            cost,
+            salt,
+            hash: bcrypt(password, cost, &salt),
+        }
+    }
+
+    /// Hashes password with a random salt.
+    pub fn generate<T>(password: &[u8], cost: u32, rng: &mut T) -> Self
+    where
+        T: CryptoRng,
+    {
+        let mut salt = [0; BCRYPT_SALT_BYTES];
+        rng.fill_bytes(&mut salt);
+        Self::new(password, cost, salt)
+    }
+
+    pub fn parse(s: &str) -> Result<Self, BcryptError> {
+        let s = s.as_bytes();
+        if s.len() != 60 || s[0] != b'$' || s[3] != b'$' || s[6] != b'$' {
+            return Err(BcryptError::Format);
+        }
+
+        let version = match &s[1..3] {
+            b"2a" => BcryptVersion::V2a,
+            b"2b" => BcryptVersion::V2b,
+            b"2y" => BcryptVersion::V2y,
+            _ => return Err(BcryptError::UnsupportedVersion),
+        };
+
+        if !s[4..6].iter().all(u8::is_ascii_digit) {
+            return Err(BcryptError::Format);
+        }
+        let cost = ((s[4] - b'0') * 10 + (s[5] - b'0')) as u32;
+        if !(BCRYPT_MIN_COST..=BCRYPT_MAX_COST).contains(&cost) {
+            return Err(BcryptError::Cost(cost));
+        }
+
+        let mut salt = [0; BCRYPT_SALT_BYTES];
+        bcrypt_b64_decode(&s[7..29], &mut salt)?;
+        let mut hash = [0; BCRYPT_HASH_BYTES];
+        bcrypt_b64_decode(&s[29..], &mut hash)?;
+
+        Ok(Self {
+            version,
+            cost,
+            salt,
+            hash,
+        })
+    }
+
+    pub fn version(&self) -> BcryptVersion {
+        self.version
+    }
+
+    /// the log2 of the number of key schedule rounds, from 4 to 31
+    pub fn cost(&self) -> u32 {
+        self.cost
+    }
+
+    pub fn salt(&self) -> &[u8; BCRYPT_SALT_BYTES] {
+        &self.salt
+    }
+
+    pub fn hash(&self) -> &[u8; BCRYPT_HASH_BYTES] {
+        &self.hash
+    }
+
+    /// Reruns bcrypt with the cost and salt of this hash, and compares in
+    /// constant time.
+    pub fn verify(&self, password: &[u8]) -> bool {
+        ct_eq(&bcrypt(password, self.cost, &self.salt), &self.hash)
+    }
+}
+
+impl fmt::Display for BcryptHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "${}${:02}${}{}",
+            self.version.prefix(),
+            self.cost,
+            bcrypt_b64_encode(&self.salt),
+            bcrypt_b64_encode(&self.hash)
+        )
+    }
+}
+
+/// Checks password against a stored bcrypt hash. Returns Ok(false) for a
+/// wrong password, and an error if the hash can not be parsed.
+pub fn bcrypt_verify(password: &[u8], hash: &str) -> Result<bool, BcryptError> {
+    Ok(BcryptHash::parse(hash)?.verify(password))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kdf::{BcryptError, BcryptHash, BcryptVersion, bcrypt_verify};
+
+    #[test]
+    fn bcrypt_other_implementations() {
+        let cases: [(&[u8], &str); 5] = [
+            // PHP online tool, Python, node.js
+            (
+                b"password",
+                "$2a$04$UuTkLRZZ6QofpDOlMz32MuuxEHA43WOemOYHPz6.SjsVsyO1tDU96",
+            ),
+            (
+                b"correctbatteryhorsestapler",
+                "$2b$04$EGdrhbKUv8Oc9vGiXX0HQOxSg445d458Muh7DAHskb6QbtCvdxcie",
+            ),
+            (
+                b"correctbatteryhorsestapler",
+                "$2a$04$n4Uy0eSnMfvnESYL.bLwuuj0U/ETSsoTpRT9GVk5bektyVVa5xnIi",
+            ),
+            // Go, with a binary password
+            (
+                &[
+                    29, 225, 195, 167, 223, 236, 85, 195, 114, 227, 7, 0, 209, 239, 189, 24, 51,
+                    105, 124, 168, 151, 75, 144, 64, 198, 197, 196, 4, 241, 97, 110, 135,
+                ],
+                "$2a$04$tjARW6ZON3PhrAIRW2LG/u9aDw5eFdstYLR8nFCNaOQmsH9XD23w.",
+            ),
+            (
+                b"My S3cre7 P@55w0rd!",
+                "$2b$05$HlFShUxTu4ZHHfOLJwfmCeDj/kuKFKboanXtDJXxCC7aIPTUgxNDe",
+            ),
+        ];
+
+        for (password, hash) in cases {
+            assert_eq!(bcrypt_verify(password, hash), Ok(true));
+            assert_eq!(bcrypt_verify(b"wrong", hash), Ok(false));
+            assert_eq!(BcryptHash::parse(hash).unwrap().to_string(), hash);
+        }
+    }
+
+    #[test]
+    fn bcrypt_fixed_salt() {
+        let res1 = BcryptHash::new(b"hunter2", 4, [0; 16]).to_string();
+        let exp1 = "$2b$04$......................kqsx/caaQ2ooGaGUPBIUMKYjFvRA7Wi";
+        assert_eq!(res1, exp1);
+
+        let res2 = BcryptHash::new(b"", 4, [7; 16]).to_string();
+        let exp2 = "$2b$04$/uaF/uaF/uaF/uaF/uaF/ug0IxynTgi9KGDC9Ai.2CD1wsFqroU9q";
+        assert_eq!(res2, exp2);
+    }
+
+    #[test]
+    fn bcrypt_truncation() {
+        // the NUL byte still fits after 71 bytes, but not after 72
+        let res1 = BcryptHash::new(&[b'x'; 71], 4, [7; 16]).to_string();
+        let exp1 = "$2b$04$/uaF/uaF/uaF/uaF/uaF/uWpbX63RAujbSlvgeKWJrl28yVhMPQta";
+        assert_eq!(res1, exp1);
+        let res2 = BcryptHash::new(&[b'x'; 72], 4, [7; 16]).to_string();
+        let exp2 = "$2b$04$/uaF/uaF/uaF/uaF/uaF/u1VyqlrbA0wm3YE5mSLANMC4rhiQyiQW";
+        assert_eq!(res2, exp2);
+
+        let hash = "$2a$05$......................YgIDy4hFBdVlc/6LHnD9mX488r9cLd2";
+        assert_eq!(bcrypt_verify(&[b'x'; 72], hash), Ok(true));
+        assert_eq!(bcrypt_verify(&[b'x'; 100], hash), Ok(true));
+        assert_eq!(bcrypt_verify(&[b'x'; 71], hash), Ok(false));
+    }
+
+    #[test]
+    fn bcrypt_versions() {
+        let hash = "$2b$04$......................kqsx/caaQ2ooGaGUPBIUMKYjFvRA7Wi";
+        for (prefix, version) in [
+            ("$2a$", BcryptVersion::V2a),
+            ("$2b$", BcryptVersion::V2b),
+            ("$2y$", BcryptVersion::V2y),
+        ] {
+            let hash = hash.replacen("$2b$", prefix, 1);
+            let parsed = BcryptHash::parse(&hash).unwrap();
+            assert_eq!(parsed.version(), version);
+            assert_eq!(parsed.cost(), 4);
+            assert!(parsed.verify(b"hunter2"));
+            assert_eq!(parsed.to_string(), hash);
+        }
+
+        let hash = hash.replacen("$2b$", "$2x$", 1);
+        assert_eq!(
+            BcryptHash::parse(&hash),
+            Err(BcryptError::UnsupportedVersion)
+        );
+    }
+
+    #[test]
+    fn bcrypt_parse_errors() {
+        let cases = [
+            (
+                "$2y$12$L6Bc/AlTQHyd9lGEZyOFLPHNgyxeEPfgYfBCVxJ7JIlwxyVU3u",
+                BcryptError::Format,
+            ),
+            (
+                "!2y$12$L6Bc/AlTQHyd9liGgGEZyOFLPHNgyxeEPfgYfBCVxJ7JIlwxyVU3u",
+                BcryptError::Format,
+            ),
+            (
+                "$2a$ab$n4Uy0eSnMfvnESYL.bLwuuj0U/ETSsoTpRT9GVk5bektyVVa5xnIi",
+                BcryptError::Format,
+            ),
+            (
+                "$2a$04$n4Uy0eSnMfvnESYL.bLwuuj0U/ETSsoTpRT9GVk$5bektyVVa5xnI",
+                BcryptError::Format,
+            ),
+            (
+                "$2a$03$n4Uy0eSnMfvnESYL.bLwuuj0U/ETSsoTpRT9GVk5bektyVVa5xnIi",
+                BcryptError::Cost(3),
+            ),
+            (
+                "$2a$32$n4Uy0eSnMfvnESYL.bLwuuj0U/ETSsoTpRT9GVk5bektyVVa5xnIi",
+                BcryptError::Cost(32),
+            ),
+        ];
+
+        for (hash, error) in cases {
+            assert_eq!(bcrypt_verify(b"password", hash), Err(error), "{hash}");
+        }
+    }
+
+    #[test]
+    fn bcrypt_generate_and_rehash() {
+        let old = BcryptHash::generate(b"hunter2", 4, &mut rand::rng());
+        assert!(bcrypt_verify(b"hunter2", &old.to_string()).unwrap());
+
+        // raise the cost the next time the user logs in
+        let stored = BcryptHash::parse(&old.to_string()).unwrap();
+        assert!(stored.verify(b"hunter2") && stored.cost() < 5);
+        let new = BcryptHash::generate(b"hunter2", 5, &mut rand::rng());
+        assert_ne!(new.salt(), old.salt());
+        assert!(new.verify(b"hunter2"));
+    }
+}
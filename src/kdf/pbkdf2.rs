@@ -0,0 +1,170 @@
+use std::convert::Infallible;
+
+use crate::{
+    hash::{DigestError, FixedOutputHash},
+    mac::Hmac,
+};
+
+/// PBKDF2 (RFC 8018) with HMAC over H, filling out with the derived key.
+/// Every output block of the hash size costs iterations HMAC calls. With
+/// SHA-1, a collision attack in the salt gives the key of the safe hash, see
+/// try_pbkdf2() to reject it.
+pub fn pbkdf2<H: FixedOutputHash + Default>(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    out: &mut [u8],
+) {
+    let Ok(()) = derive::<H, Infallible>(password, salt, iterations, out, |mac| Ok(mac.finalize()));
+}
+
+/// pbkdf2(), but with an error if the hash refuses the input.
+pub fn try_pbkdf2<H: FixedOutputHash + Default>(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    out: &mut [u8],
+) -> Result<(), DigestError> {
+    derive::<H, DigestError>(password, salt, iterations, out, Hmac::try_finalize)
+}
+
+fn derive<H: FixedOutputHash + Default, E>(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    out: &mut [u8],
+    finalize: impl Fn(Hmac<H>) -> Result<Vec<u8>, E>,
+) -> Result<(), E> {
+    if iterations == 0 {
+        panic!("PBKDF2 needs at least one iteration!");
+    }
+
+    // the padded password is hashed once, and the state cloned for each call
+    let prf = Hmac::<H>::new(password);
+    let block_length = prf.output_size();
+    if out.len() as u64 > u32::MAX as u64 * block_length as u64 {
+        panic!("PBKDF2 output too long! {} bytes", out.len());
+    }
+
+    for (i, chunk) in out.chunks_mut(block_length).enumerate() {
+        let mut mac = prf.clone();
+        mac.update(salt);
+        mac.update(&(i as u32 + 1).to_be_bytes());
+        let mut u = finalize(mac)?;
+        let mut t = u.clone();
+
+        for _ in 1..iterations {
+            let mut mac = prf.clone();
+            mac.update(&u);
+            u = finalize(mac)?;
+            for (a, b) in t.iter_mut().zip(&u) {
+                *a ^= b;
+            }
+        }
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::{Blake2b, FixedOutputHash, Sha3_256, Sha256, Sha512},
+        kdf::pbkdf2,
+        slice::u8_to_hexstr,
+    };
+
+    fn derive<H: FixedOutputHash + Default>(
+        password: &[u8],
+        salt: &[u8],
+        iterations: u32,
+        length: usize,
+    ) -> String {
+        let mut out = vec![0; length];
+        pbkdf2::<H>(password, salt, iterations, &mut out);
+        u8_to_hexstr(&out)
+    }
+
+    #[test]
+    #[cfg(feature = "legacy")]
+    fn pbkdf2_rfc6070() {
+        use crate::hash::Sha1;
+
+        let cases: [(&[u8], &[u8], u32, usize, &str); 5] = [
+            (
+                b"password",
+                b"salt",
+                1,
+                20,
+                "0c60c80f961f0e71f3a9b524af6012062fe037a6",
+            ),
+            (
+                b"password",
+                b"salt",
+                2,
+                20,
+                "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957",
+            ),
+            (
+                b"password",
+                b"salt",
+                4096,
+                20,
+                "4b007901b765489abead49d926f721d065a429c1",
+            ),
+            (
+                b"passwordPASSWORDpassword",
+                b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+                4096,
+                25,
+                "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038",
+            ),
+            (
+                b"pass\0word",
+                b"sa\0lt",
+                4096,
+                16,
+                "56fa6aa75548099dcc37d7f03425e0c3",
+            ),
+        ];
+
+        for (password, salt, iterations, length, expected) in cases {
+            assert_eq!(derive::<Sha1>(password, salt, iterations, length), expected);
+        }
+
+        // the WPA2 pre-shared key from IEEE 802.11i, SSID "IEEE"
+        let res1 = derive::<Sha1>(b"password", b"IEEE", 4096, 32);
+        let exp1 = "f42c6fc52df0ebef9ebb4b90b38a5f902e83fe1b135a70e23aed762e9710a12e";
+        assert_eq!(res1, exp1);
+    }
+
+    #[test]
+    fn pbkdf2_sha2() {
+        // RFC 7914 section 11
+        let res1 = derive::<Sha256>(b"passwd", b"salt", 1, 64);
+        let exp1 = "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783";
+        assert_eq!(res1, exp1);
+
+        let res2 = derive::<Sha256>(b"Password", b"NaCl", 80000, 64);
+        let exp2 = "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56a1d425a1225833549adb841b51c9b3176a272bdebba1d078478f62b397f33c8d";
+        assert_eq!(res2, exp2);
+
+        // the seed of the first BIP-39 test vector, passphrase TREZOR
+        let mnemonic = [["abandon"; 11].join(" ").as_str(), "about"].join(" ");
+        let res3 = derive::<Sha512>(mnemonic.as_bytes(), b"mnemonicTREZOR", 2048, 64);
+        let exp3 = "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04";
+        assert_eq!(res3, exp3);
+    }
+
+    #[test]
+    fn pbkdf2_other_hashes() {
+        let res1 = derive::<Blake2b>(b"password", b"salt", 2, 100);
+        let exp1 = "40b77cc2ee4b4c44eeb5babc299be14af5670e39ea3ce14c0fe70e6c99369886ab4d693bad8bd811ed64c5cf65a4cc5260993e17bbf2423c77164752fcbf5a60f4eccaa53b9d64a6403100110c5ef4695b960c1b383f4f033cf4b956cd4ba70444740a42";
+        assert_eq!(res1, exp1);
+
+        let res2 = derive::<Sha3_256>(b"password", b"salt", 2, 40);
+        let exp2 =
+            "4c915baedd1773383e77fcfe38114ca7514010adec24b47290ec170208423f76f876ee35e753a3f7";
+        assert_eq!(res2, exp2);
+    }
+}
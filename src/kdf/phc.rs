@@ -0,0 +1,513 @@
+use std::fmt;
+
+use super::argon2;
+use crate::{
+    hash::{Blake2bParams, HashFunction},
+    slice::ct_eq,
+};
+
+const B64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Why a PHC string was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhcError {
+    /// not a $ separated PHC string, or a field with invalid characters
+    Format,
+    /// a salt, hash or parameter that is not valid B64
+    Base64,
+    /// an algorithm identifier this crate does not implement
+    UnknownAlgorithm(String),
+    /// a version of the algorithm this crate does not implement
+    UnsupportedVersion,
+    /// a missing, unknown or out of range parameter, salt and hash included
+    InvalidParam(String),
+}
+
+impl fmt::Display for PhcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Format => write!(f, "malformed PHC string"),
+            Self::Base64 => write!(f, "invalid B64 in PHC string"),
+            Self::UnknownAlgorithm(id) => write!(f, "unknown password hash algorithm {id}"),
+            Self::UnsupportedVersion => write!(f, "unsupported password hash version"),
+            Self::InvalidParam(name) => write!(f, "invalid password hash parameter {name}"),
+        }
+    }
+}
+
+impl std::error::Error for PhcError {}
+
+/// Upper bounds on the cost a stored hash may ask for. The string comes from
+/// a database, and without a limit a single entry can make the verifier
+/// allocate terabytes or run for days. Parameters above a limit are
+/// InvalidParam errors.
+#[derive(Clone, Copy, Debug)]
+pub struct PhcLimits {
+    max_memory: u64,
+    max_lanes: u32,
+    max_iterations: u32,
+}
+
+impl PhcLimits {
+    /// 1 GiB of memory, 64 lanes and 32 passes.
+    pub fn new() -> Self {
+        Self {
+            max_memory: 1 << 30,
+            max_lanes: 64,
+            max_iterations: 32,
+        }
+    }
+
+    /// The most memory verification may allocate, in bytes.
+    pub fn max_memory(mut self, max_memory: u64) -> Self {
+        self.max_memory = max_memory;
+        self
+    }
+
+    /// The most Argon2 lanes a hash may have.
+    pub fn max_lanes(mut self, max_lanes: u32) -> Self {
+        self.max_lanes = max_lanes;
+        self
+    }
+
+    /// The most passes over the memory a hash may ask for.
+    pub fn max_iterations(mut self, max_iterations: u32) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub(super) fn check_memory(&self, bytes: u64) -> bool {
+        bytes <= self.max_memory
+    }
+
+    pub(super) fn check_lanes(&self, lanes: u32) -> bool {
+        lanes <= self.max_lanes
+    }
+
+    pub(super) fn check_iterations(&self, iterations: u32) -> bool {
+        iterations <= self.max_iterations
+    }
+}
+
+impl Default for PhcLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A password hash in the PHC string format:
+/// $id[$v=version][$param=value(,param=value)*][$salt[$hash]]
+/// where salt and hash are B64, base64 without padding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhcString {
+    pub algorithm: String,
+    pub version: Option<u32>,
+    /// the parameters in the order they appear in the string
+    pub params: Vec<(String, String)>,
+    pub salt: Option<Vec<u8>>,
+    pub hash: Option<Vec<u8>>,
+}
+
+/// PHC identifiers and parameter names: [a-z0-9-]{1,32}
+fn is_symbol(s: &str) -> bool {
+    (1..=32).contains(&s.len())
+        && s.bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+}
+
+/// PHC parameter values: [a-zA-Z0-9/+.-]+
+fn is_param_value(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"/+.-".contains(&b))
+}
+
+/// Base64 with the standard alphabet and without padding.
+pub(super) fn b64_encode(data: &[u8]) -> String {
+    let mut result = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let mut group = [0u8; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+        for i in 0..=chunk.len() {
+            let index = (bits >> (18 - 6 * i)) & 0x3f;
+            result.push(B64_ALPHABET[index as usize] as char);
+        }
+    }
+    result
+}
+
+/// Rejects padding, other characters and unused bits that are not zero, so
+/// every byte string has exactly one encoding.
+pub(super) fn b64_decode(s: &str) -> Result<Vec<u8>, PhcError> {
+    if s.len() % 4 == 1 {
+        return Err(PhcError::Base64);
+    }
+
+    let mut result = Vec::with_capacity(s.len() * 3 / 4);
+    for chunk in s.as_bytes().chunks(4) {
+        let mut bits = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = B64_ALPHABET
+                .iter()
+                .position(|a| a == c)
+                .ok_or(PhcError::Base64)?;
+            bits |= (value as u32) << (18 - 6 * i);
+        }
+
+        let bytes = &bits.to_be_bytes()[1..chunk.len()];
+        if bits & ((1 << (24 - 8 * bytes.len())) - 1) != 0 {
+            return Err(PhcError::Base64);
+        }
+        result.extend_from_slice(bytes);
+    }
+    Ok(result)
+}
+
+impl PhcString {
+    pub fn parse(s: &str) -> Result<Self, PhcError> {
+        let mut fields = s
+            .strip_prefix('$')
+            .ok_or(PhcError::Format)?
+            .split('$')
+            .peekable();
+
+        let algorithm = fields.next().ok_or(PhcError::Format)?;
+        if !is_symbol(algorithm) {
+            return Err(PhcError::Format);
+        }
+
+        let mut version = None;
+        if let Some(v) = fields.peek().and_then(|f| f.strip_prefix("v=")) {
+            if v.is_empty() || !v.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(PhcError::Format);
+            }
+            version = Some(v.parse().map_err(|_| PhcError::Format)?);
+            fields.next();
+        }
+
+        // B64 has no '=', so a field with one is the parameter list
+        let mut params = Vec::new();
+        if let Some(list) = fields.next_if(|f| f.contains('=')) {
+            for param in list.split(',') {
+                let (name, value) = param.split_once('=').ok_or(PhcError::Format)?;
+                if !is_symbol(name) || !is_param_value(value) {
+                    return Err(PhcError::Format);
+                }
+                params.push((name.to_string(), value.to_string()));
+            }
+        }
+
+        let salt = fields.next().map(b64_decode).transpose()?;
+        let hash = fields.next().map(b64_decode).transpose()?;
+        if fields.next().is_some() {
+            return Err(PhcError::Format);
+        }
+
+        Ok(Self {
+            algorithm: algorithm.to_string(),
+            version,
+            params,
+            salt,
+            hash,
+        })
+    }
+
+    /// The value of the parameter called name.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+impl fmt::Display for PhcString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${}", self.algorithm)?;
+        if let Some(version) = self.version {
+            write!(f, "$v={version}")?;
+        }
+        for (i, (name, value)) in self.params.iter().enumerate() {
+            let separator = if i == 0 { '$' } else { ',' };
+            write!(f, "{separator}{name}={value}")?;
+        }
+        if let Some(salt) = &self.salt {
+            write!(f, "${}", b64_encode(salt))?;
+            if let Some(hash) = &self.hash {
+                write!(f, "${}", b64_encode(hash))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks password against a stored PHC string, rerunning the algorithm it
+/// names in constant time. Returns Ok(false) for a wrong password, and an
+/// error if the string is malformed or not supported.
+///
+/// The secret is the Argon2 secret, or the key of a keyed BLAKE2b digest
+/// stored as $blake2b$salt$hash, where the salt (at most 16 bytes) goes into
+/// the parameter block and the digest length is the length of the hash.
+///
+/// The cost parameters are bounded by the defaults of PhcLimits.
+pub fn verify_password(phc: &str, password: &[u8], secret: &[u8]) -> Result<bool, PhcError> {
+    verify_password_with_limits(phc, password, secret, &PhcLimits::new())
+}
+
+/// verify_password() with other bounds on the cost parameters.
+pub fn verify_password_with_limits(
+    phc: &str,
+    password: &[u8],
+    secret: &[u8],
+    limits: &PhcLimits,
+) -> Result<bool, PhcError> {
+    let phc = PhcString::parse(phc)?;
+
+    match phc.algorithm.as_str() {
+        "argon2d" | "argon2i" | "argon2id" => argon2::verify_phc(&phc, password, secret, limits),
+        "blake2b" => verify_blake2b(&phc, password, secret),
+        _ => Err(PhcError::UnknownAlgorithm(phc.algorithm)),
+    }
+}
+
+fn verify_blake2b(phc: &PhcString, password: &[u8], key: &[u8]) -> Result<bool, PhcError> {
+    if phc.version.is_some() {
+        return Err(PhcError::UnsupportedVersion);
+    }
+    if let Some((name, _)) = phc.params.first() {
+        return Err(PhcError::InvalidParam(name.clone()));
+    }
+    let salt = phc.salt.as_deref().unwrap_or_default();
+    if salt.len() > 16 {
+        return Err(PhcError::InvalidParam("salt".to_string()));
+    }
+    let expected = match phc.hash.as_deref() {
+        Some(hash) if (1..=64).contains(&hash.len()) => hash,
+        _ => return Err(PhcError::InvalidParam("hash".to_string())),
+    };
+    if key.len() > 64 {
+        return Err(PhcError::InvalidParam("key".to_string()));
+    }
+
+    let mut hasher = Blake2bParams::new()
+        .hashlen(expected.len())
+        .key(key)
+        .salt(salt)
+        .build();
+    hasher.update(password);
+    Ok(ct_eq(&hasher.digest(), expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::{Blake2bParams, HashFunction},
+        kdf::{
+            Argon2Params, Argon2Variant, PhcError, PhcLimits, PhcString,
+            phc::{b64_decode, b64_encode},
+            verify_password, verify_password_with_limits,
+        },
+    };
+
+    #[test]
+    fn b64_round_trip() {
+        let cases: [(&[u8], &str); 5] = [
+            (b"", ""),
+            (b"f", "Zg"),
+            (b"fo", "Zm8"),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg"),
+        ];
+
+        for (data, encoded) in cases {
+            assert_eq!(b64_encode(data), encoded);
+            assert_eq!(b64_decode(encoded).unwrap(), data);
+        }
+
+        // padding, a lone character, and unused bits that are set
+        for bad in ["Zg==", "Zm9vY", "Zh", "Zm9*"] {
+            assert_eq!(b64_decode(bad), Err(PhcError::Base64));
+        }
+    }
+
+    #[test]
+    fn phc_parse_and_format() {
+        let s = "$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$GpZ3sK/oH9p7VIiV56G/64Zo/8GaUw434IimaPqxwCo";
+        let phc = PhcString::parse(s).unwrap();
+        assert_eq!(phc.algorithm, "argon2id");
+        assert_eq!(phc.version, Some(19));
+        assert_eq!(phc.param("m"), Some("65536"));
+        assert_eq!(phc.param("p"), Some("1"));
+        assert_eq!(phc.param("x"), None);
+        assert_eq!(phc.salt.as_deref(), Some(&b"somesalt"[..]));
+        assert_eq!(phc.hash.as_ref().map(Vec::len), Some(32));
+        assert_eq!(phc.to_string(), s);
+
+        for s in [
+            "$md5",
+            "$scrypt$ln=15,r=8,p=1",
+            "$blake2b$c2FsdA",
+            "$x$v=1$$",
+        ] {
+            assert_eq!(PhcString::parse(s).unwrap().to_string(), s);
+        }
+
+        for bad in [
+            "argon2id$v=19",
+            "$",
+            "$Argon2id",
+            "$argon2id$v=x",
+            "$argon2id$m=,t=1",
+            "$argon2id$m=1;t=1",
+            "$argon2id$c2FsdA$aGFzaA$extra",
+        ] {
+            assert_eq!(PhcString::parse(bad), Err(PhcError::Format), "{bad}");
+        }
+    }
+
+    #[test]
+    fn phc_argon2() {
+        let stored =
+            "$argon2id$v=19$m=256,t=2,p=1$c29tZXNhbHQ$nf65EOgLrQMR/uIPnA4rEsF5h7TKyQwu9U1bMCHGi/4";
+        assert_eq!(verify_password(stored, b"password", b""), Ok(true));
+        assert_eq!(verify_password(stored, b"passwore", b""), Ok(false));
+
+        let argon2 = Argon2Params::new(Argon2Variant::Argon2i)
+            .memory(64)
+            .iterations(2)
+            .lanes(2)
+            .secret(b"pepper")
+            .associated_data(b"user 42")
+            .build();
+        let stored = argon2.hash_phc(b"hunter2", b"0123456789abcdef").unwrap();
+        assert!(
+            stored
+                .starts_with("$argon2i$v=19$m=64,t=2,p=2,data=dXNlciA0Mg$MDEyMzQ1Njc4OWFiY2RlZg$")
+        );
+        assert_eq!(verify_password(&stored, b"hunter2", b"pepper"), Ok(true));
+        assert_eq!(verify_password(&stored, b"hunter2", b""), Ok(false));
+        assert_eq!(verify_password(&stored, b"hunter3", b"pepper"), Ok(false));
+
+        let errors = [
+            (
+                "$argon2id$m=64,t=2,p=1$c29tZXNhbHQ$aGFzaA",
+                PhcError::UnsupportedVersion,
+            ),
+            (
+                "$argon2id$v=16$m=64,t=2,p=1$c29tZXNhbHQ$aGFzaA",
+                PhcError::UnsupportedVersion,
+            ),
+            (
+                "$argon2id$v=19$m=64,t=2$c29tZXNhbHQ$aGFzaA",
+                PhcError::InvalidParam("p".to_string()),
+            ),
+            (
+                "$argon2id$v=19$m=8,t=2,p=2$c29tZXNhbHQ$aGFzaA",
+                PhcError::InvalidParam("m".to_string()),
+            ),
+            (
+                "$argon2id$v=19$m=64,t=2,p=1,x=1$c29tZXNhbHQ$aGFzaA",
+                PhcError::InvalidParam("x".to_string()),
+            ),
+            (
+                "$argon2id$v=19$m=64,t=2,p=1$c2FsdA$aGFzaA",
+                PhcError::InvalidParam("salt".to_string()),
+            ),
+            (
+                "$argon2id$v=19$m=64,t=2,p=1$c29tZXNhbHQ",
+                PhcError::InvalidParam("hash".to_string()),
+            ),
+            (
+                "$scrypt$ln=4,r=8,p=1$c2FsdA$aGFzaA",
+                PhcError::UnknownAlgorithm("scrypt".to_string()),
+            ),
+        ];
+        for (stored, error) in errors {
+            assert_eq!(
+                verify_password(stored, b"password", b""),
+                Err(error),
+                "{stored}"
+            );
+        }
+    }
+
+    #[test]
+    fn phc_limits() {
+        // 4 TiB, 2^24 - 1 lanes and 2^32 - 1 passes, refused before
+        // allocating anything
+        let huge = [
+            (
+                "$argon2id$v=19$m=4294967295,t=1,p=1$c29tZXNhbHQ$aGFzaA",
+                "m",
+            ),
+            (
+                "$argon2id$v=19$m=4294967295,t=1,p=16777215$c29tZXNhbHQ$aGFzaA",
+                "p",
+            ),
+            (
+                "$argon2id$v=19$m=8,t=4294967295,p=1$c29tZXNhbHQ$aGFzaA",
+                "t",
+            ),
+        ];
+        for (stored, name) in huge {
+            assert_eq!(
+                verify_password(stored, b"password", b""),
+                Err(PhcError::InvalidParam(name.to_string()))
+            );
+        }
+
+        let stored = Argon2Params::new(Argon2Variant::Argon2id)
+            .memory(256)
+            .iterations(1)
+            .lanes(2)
+            .build()
+            .hash_phc(b"password", b"somesalt")
+            .unwrap();
+        let strict = PhcLimits::new().max_memory(128 * 1024);
+        assert_eq!(
+            verify_password_with_limits(&stored, b"password", b"", &strict),
+            Err(PhcError::InvalidParam("m".to_string()))
+        );
+        let strict = PhcLimits::new().max_lanes(1);
+        assert_eq!(
+            verify_password_with_limits(&stored, b"password", b"", &strict),
+            Err(PhcError::InvalidParam("p".to_string()))
+        );
+        let strict = PhcLimits::new().max_iterations(0);
+        assert_eq!(
+            verify_password_with_limits(&stored, b"password", b"", &strict),
+            Err(PhcError::InvalidParam("t".to_string()))
+        );
+        let loose = PhcLimits::new().max_memory(256 * 1024);
+        assert_eq!(
+            verify_password_with_limits(&stored, b"password", b"", &loose),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn phc_keyed_blake2b() {
+        let key = b"server side key";
+        let mut hasher = Blake2bParams::new()
+            .hashlen(32)
+            .key(key)
+            .salt(b"per user salt")
+            .build();
+        hasher.update(b"correct horse");
+
+        let stored = PhcString {
+            algorithm: "blake2b".to_string(),
+            version: None,
+            params: Vec::new(),
+            salt: Some(b"per user salt".to_vec()),
+            hash: Some(hasher.digest()),
+        }
+        .to_string();
+        assert!(stored.starts_with("$blake2b$cGVyIHVzZXIgc2FsdA$"));
+
+        assert_eq!(verify_password(&stored, b"correct horse", key), Ok(true));
+        assert_eq!(verify_password(&stored, b"correct horse", b""), Ok(false));
+        assert_eq!(verify_password(&stored, b"battery staple", key), Ok(false));
+    }
+}
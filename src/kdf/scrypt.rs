@@ -0,0 +1,323 @@
+use std::fmt;
+
+use super::pbkdf2;
+use crate::hash::Sha256;
+
+// words in one 64 byte Salsa20 block
+const SALSA_WORDS: usize = 16;
+
+/// Why scrypt refused to run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScryptError {
+    /// N, r, p or the output length is out of range
+    InvalidParams(&'static str),
+    /// the memory for N and r is above the limit, or could not be allocated
+    MemoryLimit { required: u64, limit: u64 },
+}
+
+impl fmt::Display for ScryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidParams(reason) => write!(f, "invalid scrypt parameters: {reason}"),
+            Self::MemoryLimit { required, limit } => write!(
+                f,
+                "scrypt needs {required} bytes of memory, the limit is {limit}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScryptError {}
+
+/// The scrypt key derivation function (RFC 7914): PBKDF2-HMAC-SHA256 around
+/// p runs of ROMix, which needs 128 * r * N bytes of memory.
+#[derive(Clone, Debug)]
+pub struct Scrypt {
+    n: u64,
+    r: u32,
+    p: u32,
+    max_memory: u64,
+}
+
+impl Scrypt {
+    /// N is the CPU/memory cost, a power of two above 1. r is the block size
+    /// and p the parallelization. age and Ethereum keystores use r = 8, p = 1.
+    pub fn new(n: u64, r: u32, p: u32) -> Result<Self, ScryptError> {
+        if n < 2 || !n.is_power_of_two() {
+            return Err(ScryptError::InvalidParams(
+                "N has to be a power of two above 1",
+            ));
+        }
+        if r == 0 || p == 0 {
+            return Err(ScryptError::InvalidParams("r and p have to be at least 1"));
+        }
+        if r as u64 * p as u64 >= 1 << 30 {
+            return Err(ScryptError::InvalidParams("r * p has to be below 2^30"));
+        }
+        // N < 2^(128 * r / 8)
+        if 16 * (r as u64) < 64 && n >= 1 << (16 * r) {
+            return Err(ScryptError::InvalidParams("N is too large for r"));
+        }
+
+        Ok(Self {
+            n,
+            r,
+            p,
+            max_memory: 1 << 30,
+        })
+    }
+
+    /// The most memory scrypt may allocate, in bytes. Defaults to 1 GiB,
+    /// enough for N = 2^20 with r = 8.
+    pub fn max_memory(mut self, max_memory: u64) -> Self {
+        self.max_memory = max_memory;
+        self
+    }
+
+    /// The memory needed for these parameters, in bytes. u64::MAX if that
+    /// does not even fit a u64.
+    pub fn required_memory(&self) -> u64 {
+        let block = 128 * self.r as u64;
+        // V, plus the p blocks of B
+        block
+            .checked_mul(self.n)
+            .and_then(|v| v.checked_add(block * self.p as u64))
+            .unwrap_or(u64::MAX)
+    }
+
+    /// Derives an output_length byte key from password and salt.
+    pub fn derive(
+        &self,
+        password: &[u8],
+        salt: &[u8],
+        output_length: usize,
+    ) -> Result<Vec<u8>, ScryptError> {
+        if output_length == 0 || output_length as u64 > u32::MAX as u64 * 32 {
+            return Err(ScryptError::InvalidParams(
+                "the output length has to be from 1 to (2^32 - 1) * 32",
+            ));
+        }
+        let required = self.required_memory();
+        let too_much = ScryptError::MemoryLimit {
+            required,
+            limit: self.max_memory,
+        };
+        if required > self.max_memory || required > usize::MAX as u64 {
+            return Err(too_much);
+        }
+
+        let block_words = 32 * self.r as usize;
+        let Some(v_words) = block_words.checked_mul(self.n as usize) else {
+            return Err(too_much);
+        };
+        let mut v: Vec<u32> = Vec::new();
+        if v.try_reserve_exact(v_words).is_err() {
+            return Err(too_much);
+        }
+        v.resize(v_words, 0);
+
+        let mut b = vec![0; block_words * 4 * self.p as usize];
+        pbkdf2::<Sha256>(password, salt, 1, &mut b);
+
+        let mut x = vec![0; block_words];
+        for chunk in b.chunks_exact_mut(block_words * 4) {
+            for (word, bytes) in x.iter_mut().zip(chunk.chunks_exact(4)) {
+                *word = u32::from_le_bytes(bytes.try_into().expect("len 4"));
+            }
+            self.ro_mix(&mut x, &mut v);
+            for (bytes, word) in chunk.chunks_exact_mut(4).zip(&x) {
+                bytes.copy_from_slice(&word.to_le_bytes());
+            }
+        }
+
+        let mut result = vec![0; output_length];
+        pbkdf2::<Sha256>(password, &b, 1, &mut result);
+        Ok(result)
+    }
+
+    /// ROMix: fill v with N BlockMix steps from x, then take N more steps
+    /// mixing in the v entries that x points to.
+    fn ro_mix(&self, x: &mut [u32], v: &mut [u32]) {
+        let block_words = x.len();
+        let mut scratch = vec![0; block_words];
+
+        for entry in v.chunks_exact_mut(block_words) {
+            entry.copy_from_slice(x);
+            block_mix(x, &mut scratch);
+        }
+
+        for _ in 0..self.n {
+            // Integerify, the first 8 bytes of the last 64 byte block
+            let last = block_words - SALSA_WORDS;
+            let j = (x[last] as u64 | (x[last + 1] as u64) << 32) & (self.n - 1);
+            let entry = &v[j as usize * block_words..(j as usize + 1) * block_words];
+            for (a, b) in x.iter_mut().zip(entry) {
+                *a ^= b;
+            }
+            block_mix(x, &mut scratch);
+        }
+    }
+}
+
+/// The Salsa20/8 core, 8 rounds of Salsa20 added to the input.
+fn salsa20_8(b: &mut [u32; SALSA_WORDS]) {
+    fn quarter_round(x: &mut [u32; SALSA_WORDS], a: usize, b: usize, c: usize, d: usize) {
+        x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+        x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+        x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+        x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+    }
+
+    let mut x = *b;
+    for _ in 0..4 {
+        // columns
+        quarter_round(&mut x, 0, 4, 8, 12);
+        quarter_round(&mut x, 5, 9, 13, 1);
+        quarter_round(&mut x, 10, 14, 2, 6);
+        quarter_round(&mut x, 15, 3, 7, 11);
+        // rows
+        quarter_round(&mut x, 0, 1, 2, 3);
+        quarter_round(&mut x, 5, 6, 7, 4);
+        quarter_round(&mut x, 10, 11, 8, 9);
+        quarter_round(&mut x, 15, 12, 13, 14);
+    }
+
+    for i in 0..SALSA_WORDS {
+        b[i] = b[i].wrapping_add(x[i]);
+    }
+}
+
+/// BlockMix with Salsa20/8 over the 2r blocks of b. The outputs of the even
+/// blocks go to the first half and the odd ones to the second half.
+fn block_mix(b: &mut [u32], scratch: &mut [u32]) {
+    let blocks = b.len() / SALSA_WORDS;
+    let mut x: [u32; SALSA_WORDS] = b[b.len() - SALSA_WORDS..].try_into().expect("len 16");
+
+    for (i, block) in b.chunks_exact(SALSA_WORDS).enumerate() {
+        for (a, b) in x.iter_mut().zip(block) {
+            *a ^= b;
+        }
+        salsa20_8(&mut x);
+
+        let position = (i / 2 + (i % 2) * blocks / 2) * SALSA_WORDS;
+        scratch[position..position + SALSA_WORDS].copy_from_slice(&x);
+    }
+    b.copy_from_slice(scratch);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        kdf::{Scrypt, ScryptError, scrypt::salsa20_8},
+        slice::{decode_hex, u8_to_hexstr},
+    };
+
+    #[test]
+    fn basic_salsa20_8() {
+        // RFC 7914 section 8
+        let input = decode_hex(
+            "7e879a214f3ec9867ca940e641718f26baee555b8c61c1b50df846116dcd3b1dee24f319df9b3d8514121e4b5ac5aa3276021d2909c74829edebc68db8b8c25e",
+        );
+        let mut b = [0u32; 16];
+        for (word, bytes) in b.iter_mut().zip(input.chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+        salsa20_8(&mut b);
+
+        let res1 = u8_to_hexstr(&b.iter().flat_map(|w| w.to_le_bytes()).collect::<Vec<u8>>());
+        let exp1 = "a41f859c6608cc993b81cacb020cef05044b2181a2fd337dfd7b1c6396682f29b4393168e3c9e6bcfe6bc5b7a06d96bae424cc102c91745c24ad673dc7618f81";
+        assert_eq!(res1, exp1);
+    }
+
+    #[test]
+    fn basic_scrypt() {
+        // RFC 7914 section 12, without the N = 2^20 case
+        let cases: [(&[u8], &[u8], u64, u32, u32, &str); 3] = [
+            (
+                b"",
+                b"",
+                16,
+                1,
+                1,
+                "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906",
+            ),
+            (
+                b"password",
+                b"NaCl",
+                1024,
+                8,
+                16,
+                "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b3731622eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640",
+            ),
+            (
+                b"pleaseletmein",
+                b"SodiumChloride",
+                16384,
+                8,
+                1,
+                "7023bdcb3afd7348461c06cd81fd38ebfda8fbba904f8e3ea9b543f6545da1f2d5432955613f0fcf62d49705242a9af9e61e85dc0d651e40dfcf017b45575887",
+            ),
+        ];
+
+        for (password, salt, n, r, p, expected) in cases {
+            let scrypt = Scrypt::new(n, r, p).unwrap();
+            let res1 = u8_to_hexstr(&scrypt.derive(password, salt, 64).unwrap());
+            assert_eq!(res1, expected);
+        }
+    }
+
+    #[test]
+    fn scrypt_errors() {
+        assert!(matches!(
+            Scrypt::new(1000, 8, 1),
+            Err(ScryptError::InvalidParams(_))
+        ));
+        assert!(matches!(
+            Scrypt::new(1, 8, 1),
+            Err(ScryptError::InvalidParams(_))
+        ));
+        assert!(matches!(
+            Scrypt::new(1024, 0, 1),
+            Err(ScryptError::InvalidParams(_))
+        ));
+        assert!(matches!(
+            Scrypt::new(1024, 1 << 15, 1 << 15),
+            Err(ScryptError::InvalidParams(_))
+        ));
+        // N has to be below 2^16 for r = 1
+        assert!(matches!(
+            Scrypt::new(1 << 16, 1, 1),
+            Err(ScryptError::InvalidParams(_))
+        ));
+
+        // age's maximum work factor, 4 GiB
+        let scrypt = Scrypt::new(1 << 22, 8, 1).unwrap();
+        assert_eq!(
+            scrypt.derive(b"password", b"salt", 32),
+            Err(ScryptError::MemoryLimit {
+                required: (1 << 32) + 1024,
+                limit: 1 << 30
+            })
+        );
+
+        // 128 * r * N does not fit a u64, even with no memory limit
+        let scrypt = Scrypt::new(1 << 62, 1 << 20, 1).unwrap();
+        assert_eq!(scrypt.required_memory(), u64::MAX);
+        assert!(matches!(
+            scrypt.max_memory(u64::MAX).derive(b"password", b"salt", 32),
+            Err(ScryptError::MemoryLimit { .. })
+        ));
+        let scrypt = Scrypt::new(1 << 40, 1 << 29, 1).unwrap();
+        assert!(matches!(
+            scrypt.derive(b"password", b"salt", 32),
+            Err(ScryptError::MemoryLimit { .. })
+        ));
+
+        let scrypt = Scrypt::new(16, 1, 1).unwrap().max_memory(2048);
+        assert!(scrypt.derive(b"password", b"salt", 32).is_err());
+        assert!(matches!(
+            scrypt.max_memory(4096).derive(b"password", b"salt", 0),
+            Err(ScryptError::InvalidParams(_))
+        ));
+    }
+}
@@ -2,7 +2,9 @@
 
 pub mod asymmetric;
 pub mod hash;
+pub mod kdf;
 pub mod mac;
+pub mod merkle;
 
 mod arith;
 mod slice;
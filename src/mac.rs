@@ -1,7 +1,69 @@
+mod blake2b;
+mod ghash;
+mod hmac;
 mod poly1305;
+mod polyval;
+#[cfg(test)]
+mod test_util;
+
+use std::fmt;
+
+use crate::slice::ct_eq;
+
+pub use blake2b::Blake2bMac;
+pub use ghash::Ghash;
+pub use hmac::Hmac;
+pub use poly1305::{
+    Poly1305, Poly1305Backend, Poly1305Radix26, Poly1305Radix44, Poly1305Radix44x4,
+    Poly1305Radix64, poly1305,
+};
+pub use polyval::Polyval;
+
+// KMAC is a Keccak based hash first, so it lives with SHA-3 in hash
+pub use crate::hash::Kmac;
+
+/// The tag does not match the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MacError;
+
+impl fmt::Display for MacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MAC tag mismatch")
+    }
+}
+
+impl std::error::Error for MacError {}
+
+/// A MAC whose key may be used for many messages. For ones that must get a
+/// fresh key for every message, see OneTimeAuthenticator.
+pub trait Mac: Sized {
+    fn new_from_key(key: &[u8]) -> Self;
+    fn update(&mut self, message: &[u8]);
+    fn finalize(self) -> Vec<u8>;
+    /// Checks the tag in constant time.
+    fn verify(self, tag: &[u8]) -> Result<(), MacError> {
+        if ct_eq(&self.finalize(), tag) {
+            Ok(())
+        } else {
+            Err(MacError)
+        }
+    }
+    /// Forgets the message so far, to start a new one with the same key.
+    fn reset(&mut self);
+}
 
 pub trait OneTimeAuthenticator {
     fn update(&mut self, message: &[u8]);
     fn finalize(self) -> Vec<u8>;
-    fn verify(self, tag: &[u8]);
+    /// Checks the tag in constant time.
+    fn verify(self, tag: &[u8]) -> Result<(), MacError>
+    where
+        Self: Sized,
+    {
+        if ct_eq(&self.finalize(), tag) {
+            Ok(())
+        } else {
+            Err(MacError)
+        }
+    }
 }
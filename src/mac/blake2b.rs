@@ -0,0 +1,85 @@
+use crate::{
+    hash::{Blake2b, FixedOutputHash, HashFunction},
+    mac::Mac,
+};
+
+/// Keyed Blake2b (RFC 7693 section 2.9) as a MAC. The key is the first
+/// block of the message, so it is safe to reuse for many messages.
+#[derive(Clone)]
+pub struct Blake2bMac {
+    hasher: Blake2b,
+}
+
+impl Blake2bMac {
+    /// A MAC with a tag of hashlen bytes (up to 64) and a key of 1 to 64
+    /// bytes.
+    pub fn with_length(key: &[u8], hashlen: usize) -> Self {
+        if key.is_empty() {
+            panic!("Blake2b MAC needs a key!");
+        }
+
+        Self {
+            hasher: Blake2b::new_keyed(hashlen, key),
+        }
+    }
+
+    /// length of the tag in bytes
+    pub fn output_size(&self) -> usize {
+        self.hasher.output_size()
+    }
+}
+
+impl Mac for Blake2bMac {
+    /// The full 64 byte tag.
+    fn new_from_key(key: &[u8]) -> Self {
+        Self::with_length(key, 64)
+    }
+
+    fn update(&mut self, message: &[u8]) {
+        self.hasher.update(message);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.hasher.digest()
+    }
+
+    fn reset(&mut self) {
+        self.hasher.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mac::{Blake2bMac, Mac, MacError, test_util::tag},
+        slice::{decode_hex, u8_to_hexstr},
+    };
+
+    #[test]
+    fn blake2b_mac_kat() {
+        // the first and last keyed entries of blake2b-kat.txt
+        let key: Vec<u8> = (0..64).collect();
+        let message: Vec<u8> = (0..255).collect();
+        let res1 = tag::<Blake2bMac>(&key, b"");
+        let res2 = tag::<Blake2bMac>(&key, &message);
+        let exp1 = "10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568";
+        let exp2 = "142709d62e28fcccd0af97fad0f8465b971e82201dc51070faa0372aa43e92484be1c1e73ba10906d5d1853db6a4106e0a7bf9800d373d6dee2d46d62ef2a461";
+        assert_eq!(u8_to_hexstr(&res1), exp1);
+        assert_eq!(u8_to_hexstr(&res2), exp2);
+    }
+
+    #[test]
+    fn blake2b_mac_reset_and_verify() {
+        let mut mac = Blake2bMac::with_length(b"key", 32);
+        assert_eq!(mac.output_size(), 32);
+        mac.update(b"The quick brown fox jumps over the lazy dog");
+        mac.reset();
+        mac.update(b"second message");
+
+        let tag = decode_hex("6eff22b75bbfc1aeacba8c71698d34f239fe281fae50cbe519410ce1ac30026c");
+        assert_eq!(mac.clone().verify(&tag), Ok(()));
+        assert_eq!(mac.clone().verify(&tag[..16]), Err(MacError));
+        mac.update(b"!");
+        assert_eq!(mac.verify(&tag), Err(MacError));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::mac::{OneTimeAuthenticator, polyval::Polyval};
+
+/// The GHASH universal hash from AES-GCM (NIST SP 800-38D). Like Poly1305,
+/// a key must only authenticate one message. A final partial block is padded
+/// with zeros.
+///
+/// GHASH is POLYVAL with the bits of each block reversed (RFC 8452 appendix
+/// A): the blocks and the tag are byte reversed, and H is multiplied by x.
+#[derive(Clone)]
+pub struct Ghash {
+    polyval: Polyval,
+}
+
+impl Ghash {
+    /// GHASH with the 16 byte key H, the block cipher applied to zeros.
+    pub fn new(key: &[u8; 16]) -> Self {
+        let h = u128::from_be_bytes(*key);
+        // multiply by x, reducing by x^128 + x^127 + x^126 + x^121 + 1
+        let carry = (h >> 127).wrapping_neg();
+        let h = (h << 1) ^ (carry & (1 | 0xc2 << 120));
+
+        Self {
+            polyval: Polyval::from_element(h),
+        }
+    }
+}
+
+impl OneTimeAuthenticator for Ghash {
+    fn update(&mut self, message: &[u8]) {
+        self.polyval
+            .update_with(message, |block| u128::from_be_bytes(*block));
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.polyval
+            .finish(|block| u128::from_be_bytes(*block))
+            .to_be_bytes()
+            .to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mac::{Ghash, MacError, OneTimeAuthenticator},
+        slice::{decode_hex, u8_to_hexstr},
+    };
+
+    fn ghash(key: &str, message: &[u8]) -> String {
+        let mut mac = Ghash::new(&decode_hex(key).try_into().unwrap());
+        mac.update(message);
+        u8_to_hexstr(&mac.finalize())
+    }
+
+    #[test]
+    fn ghash_rfc8452() {
+        // appendix A, the same input as for POLYVAL
+        let key = "25629347589242761d31f826ba4b757b";
+        let message =
+            decode_hex("4f4f95668c83dfb6401762bb2d01a262d1a24ddd2721d006bbe45f20d3c9f362");
+        assert_eq!(ghash(key, &message), "bd9b3997046731fb96251b91f9c99d7a");
+    }
+
+    #[test]
+    fn ghash_gcm() {
+        // AES-GCM test case 2: one ciphertext block and the length block
+        let key = "66e94bd4ef8a2c3b884cfa59ca342b2e";
+        let message =
+            decode_hex("0388dace60b6a392f328c2b971b2fe7800000000000000000000000000000080");
+        assert_eq!(ghash(key, &message), "f38cbb1ad69223dcc3457ae5b6b0f885");
+    }
+
+    #[test]
+    fn ghash_partial_blocks() {
+        // from the ghash crate, padded with zeros
+        let message: Vec<u8> = (0..100).collect();
+        let res1 = ghash("ffffffffffffffffffffffffffffffff", &message);
+        assert_eq!(res1, "824c0affb08deaac17c9abdc4bd7841e");
+        let res2 = ghash("25629347589242761d31f826ba4b757b", &message[..33]);
+        assert_eq!(res2, "a96b6cb33019f5365a0d00998789fe06");
+
+        let mut mac = Ghash::new(&[0xff; 16]);
+        for piece in message.chunks(7) {
+            mac.update(piece);
+        }
+        let tag = decode_hex("824c0affb08deaac17c9abdc4bd7841e");
+        assert_eq!(mac.clone().verify(&tag), Ok(()));
+        mac.update(b"!");
+        assert_eq!(mac.verify(&tag), Err(MacError));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{
+    hash::{DigestError, FixedOutputHash},
+    mac::{Mac, MacError},
+    slice::ct_eq,
+};
+
+/// HMAC (RFC 2104) over any hash with a known block size:
+/// H((K ^ opad) || H((K ^ ipad) || message)).
+#[derive(Clone)]
+pub struct Hmac<H: FixedOutputHash> {
+    // already fed with the padded key, cloning a fresh Hmac skips that block
+    inner: H,
+    outer: H,
+    // inner as it was before the message, for reset
+    keyed_inner: H,
+}
+
+impl<H: FixedOutputHash + Default> Hmac<H> {
+    pub fn new(key: &[u8]) -> Self {
+        Self::with_hasher(H::default(), key)
+    }
+}
+
+impl<H: FixedOutputHash> Hmac<H> {
+    /// HMAC with (clones of) hasher, which may carry settings like the
+    /// output length. Keys longer than the block size are hashed first.
+    pub fn with_hasher(hasher: H, key: &[u8]) -> Self {
+        let mut block = vec![0; H::BLOCK_SIZE];
+        if key.len() > H::BLOCK_SIZE {
+            let mut key_hasher = hasher.clone();
+            key_hasher.update(key);
+            let hashed = key_hasher.digest();
+            block[..hashed.len()].copy_from_slice(&hashed);
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+
+        let mut inner = hasher.clone();
+        inner.update(&block.iter().map(|b| b ^ 0x36).collect::<Vec<u8>>());
+        let mut outer = hasher;
+        outer.update(&block.iter().map(|b| b ^ 0x5c).collect::<Vec<u8>>());
+        Self {
+            keyed_inner: inner.clone(),
+            inner,
+            outer,
+        }
+    }
+
+    pub fn update(&mut self, message: &[u8]) {
+        self.inner.update(message);
+    }
+
+    /// Forgets the message so far, to start a new one with the same key.
+    pub fn reset(&mut self) {
+        self.inner = self.keyed_inner.clone();
+    }
+
+    /// length of the tag in bytes, the output size of the hash
+    pub fn output_size(&self) -> usize {
+        self.outer.output_size()
+    }
+
+    /// With SHA-1, a collision attack in the message gives the tag of the
+    /// safe hash, see try_finalize() to reject it.
+    pub fn finalize(self) -> Vec<u8> {
+        let mut outer = self.outer;
+        outer.update(&self.inner.digest());
+        outer.digest()
+    }
+
+    /// The tag, or an error if the hash refuses the message (a SHA-1
+    /// collision attack).
+    pub fn try_finalize(self) -> Result<Vec<u8>, DigestError> {
+        let mut outer = self.outer;
+        outer.update(&self.inner.try_digest()?);
+        outer.try_digest()
+    }
+
+    /// Checks a full length tag in constant time. A message the hash
+    /// refuses never verifies.
+    pub fn verify(self, tag: &[u8]) -> Result<(), MacError> {
+        match self.try_finalize() {
+            Ok(expected) if ct_eq(&expected, tag) => Ok(()),
+            _ => Err(MacError),
+        }
+    }
+}
+
+impl<H: FixedOutputHash + Default> Mac for Hmac<H> {
+    fn new_from_key(key: &[u8]) -> Self {
+        Self::new(key)
+    }
+
+    fn update(&mut self, message: &[u8]) {
+        Hmac::update(self, message);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        Hmac::finalize(self)
+    }
+
+    fn verify(self, tag: &[u8]) -> Result<(), MacError> {
+        Hmac::verify(self, tag)
+    }
+
+    fn reset(&mut self) {
+        Hmac::reset(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::{Blake2b, Sha3_256, Sha256, Sha512},
+        mac::{Hmac, Mac, MacError, test_util::tag},
+        slice::{decode_hex, u8_to_hexstr},
+    };
+
+    #[test]
+    fn hmac_rfc4231() {
+        // test cases 1, 2, 4 and 6 (a key longer than the block)
+        let cases: [(Vec<u8>, Vec<u8>, &str, &str); 4] = [
+            (
+                vec![0x0b; 20],
+                b"Hi There".to_vec(),
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+                "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cdedaa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+            ),
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?".to_vec(),
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+                "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            ),
+            (
+                decode_hex("0102030405060708090a0b0c0d0e0f10111213141516171819"),
+                vec![0xcd; 50],
+                "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+                "b0ba465637458c6990e5a8c5f61d4af7e576d97ff94b872de76f8050361ee3dba91ca5c11aa25eb4d679275cc5788063a5f19741120c4f2de2adebeb10a298dd",
+            ),
+            (
+                vec![0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+                "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f3526b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+            ),
+        ];
+
+        for (key, message, exp_sha256, exp_sha512) in cases {
+            let mut mac = Hmac::<Sha256>::new(&key);
+            mac.update(&message);
+            assert_eq!(u8_to_hexstr(&mac.finalize()), exp_sha256);
+
+            let mut mac = Hmac::<Sha512>::new(&key);
+            mac.update(&message);
+            assert_eq!(u8_to_hexstr(&mac.finalize()), exp_sha512);
+        }
+    }
+
+    #[test]
+    fn hmac_other_hashes() {
+        let message = b"The quick brown fox jumps over the lazy dog";
+
+        let mut mac = Hmac::<Blake2b>::new(b"key");
+        assert_eq!(mac.output_size(), 64);
+        mac.update(&message[..10]);
+        mac.update(&message[10..]);
+        let res1 = u8_to_hexstr(&mac.finalize());
+        let exp1 = "92294f92c0dfb9b00ec9ae8bd94d7e7d8a036b885a499f149dfe2fd2199394aaaf6b8894a1730cccb2cd050f9bcf5062a38b51b0dab33207f8ef35ae2c9df51b";
+        assert_eq!(res1, exp1);
+
+        // the key is longer than the 136 byte block of SHA3-256
+        let mut mac = Hmac::<Sha3_256>::new(&[b'k'; 200]);
+        mac.update(message);
+        let res2 = u8_to_hexstr(&mac.finalize());
+        let exp2 = "49c74846fe806e8e22acf078291b96e7c7f99904591265bd1931df7def169282";
+        assert_eq!(res2, exp2);
+    }
+
+    #[test]
+    fn hmac_verify() {
+        let tag = decode_hex("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+        let mac = Hmac::<Sha256>::new(b"Jefe");
+
+        let mut good = mac.clone();
+        good.update(b"what do ya want for nothing?");
+        assert_eq!(good.clone().verify(&tag), Ok(()));
+        // truncated tags and flipped bits are both rejected
+        assert_eq!(good.clone().verify(&tag[..16]), Err(MacError));
+        let mut flipped = tag.clone();
+        flipped[31] ^= 1;
+        assert_eq!(good.verify(&flipped), Err(MacError));
+
+        let mut bad = mac;
+        bad.update(b"what do ya want for nothing!");
+        assert_eq!(bad.verify(&tag), Err(MacError));
+    }
+
+    #[test]
+    fn hmac_as_mac() {
+        // RFC 4231 test case 2
+        let res = tag::<Hmac<Sha256>>(b"Jefe", b"what do ya want for nothing?");
+        let exp = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+        assert_eq!(u8_to_hexstr(&res), exp);
+
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_key(b"Jefe");
+        Mac::update(&mut mac, b"what do ya want for nothing?");
+        assert_eq!(Mac::verify(mac.clone(), &decode_hex(exp)), Ok(()));
+        Mac::reset(&mut mac);
+        assert_eq!(Mac::verify(mac, &decode_hex(exp)), Err(MacError));
+    }
+}
@@ -1,7 +1,12 @@
-use crate::{
-    arith,
-    mac::{OneTimeAuthenticator, poly1305},
-};
+use crate::mac::OneTimeAuthenticator;
+
+mod radix26;
+mod radix44;
+mod radix64;
+
+pub use radix26::Poly1305Radix26;
+pub use radix44::{Poly1305Radix44, Poly1305Radix44x4};
+pub use radix64::Poly1305Radix64;
 
 // magical constants are from rfc8493.
 // deriving constants from poly1305aes_test_clamp (annotated):
@@ -17,163 +22,311 @@ use crate::{
 // in big endian:                       0x0fff_fffc_0fff_fffc_0fff_fffc_0fff_ffff (BE)
 const POLY1305_R_CLAMP_LO: u64 = 0x0fff_fffc_0fff_ffff;
 const POLY1305_R_CLAMP_HI: u64 = 0x0fff_fffc_0fff_fffc;
-const POLY1305_MOD_P: (u64, u64, u64) = (0xffff_ffff_ffff_fffb, 0xffff_ffff_ffff_ffff, 0x3);
 const POLY1305_MSG_CHUNK_SIZE_BYTES: usize = 16;
 
-pub struct Poly1305 {
-    secret_r: (u64, u64),   // lo, hi
-    secret_s: (u64, u64),   // lo, hi
-    accum: (u64, u64, u64), // split into lo, mid, hi -> can exceed
+/// How the accumulator h is stored and multiplied with r mod 2^130 - 5.
+/// Poly1305 itself handles the key, the buffering and the padding.
+pub trait Poly1305Backend {
+    /// Starts with h = 0 and the already clamped r.
+    fn new(r: u128) -> Self;
+    /// h = (h + block + hibit * 2^128) * r. hibit is only unset for a short
+    /// final block, which already carries its padding.
+    fn block(&mut self, block: &[u8; 16], hibit: bool);
+    /// A run of whole blocks, all with hibit. Backends may take several
+    /// blocks at once.
+    fn blocks(&mut self, blocks: &[u8]) {
+        for block in blocks.chunks_exact(POLY1305_MSG_CHUNK_SIZE_BYTES) {
+            self.block(block.try_into().expect("len 16"), true);
+        }
+    }
+    /// h fully reduced mod 2^130 - 5, cut to 128 bits.
+    fn finalize(self) -> u128;
+}
+
+// cycles per byte on a 1 MiB message, on an Intel Xeon (x86-64, 2.0 GHz),
+// from cargo run --release --example poly1305_throughput:
+//   radix 2^64            0.85
+//   radix 2^26            1.33
+//   radix 2^44            1.23
+//   radix 2^44, 4 blocks  0.88
+// one reduction per 4 blocks takes about 30% off radix 2^44, but radix 2^64
+// needs fewer multiplies per block and stays slightly ahead
+#[cfg(target_pointer_width = "64")]
+type DefaultBackend = Poly1305Radix64;
+#[cfg(not(target_pointer_width = "64"))]
+type DefaultBackend = Poly1305Radix26;
+
+/// The Poly1305 one-time authenticator (RFC 8439). A key must never be used
+/// for more than one message. The backend defaults to the fastest one for the
+/// target, all of them give the same tags.
+pub struct Poly1305<B: Poly1305Backend = DefaultBackend> {
+    backend: B,
+    secret_s: u128,
+    // the last block is only known once finalize() is called
+    buffer: [u8; POLY1305_MSG_CHUNK_SIZE_BYTES],
+    buflen: usize,
 }
 
 impl Poly1305 {
-    fn from_le_bytes(r: [u8; 16], s: [u8; 16]) -> Self {
-        let mut clamped_r = r;
+    /// Poly1305 with the 32 byte one-time key r || s.
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self::with_backend(key)
+    }
+}
 
-        let r0 = u64::from_le_bytes(r[0..8].try_into().expect("len 8")) & POLY1305_R_CLAMP_LO;
-        let r1 = u64::from_le_bytes(r[8..16].try_into().expect("len 8")) & POLY1305_R_CLAMP_HI;
-        let s0 = u64::from_le_bytes(s[0..8].try_into().expect("len 8"));
-        let s1 = u64::from_le_bytes(s[8..16].try_into().expect("len 8"));
+impl<B: Poly1305Backend> Poly1305<B> {
+    /// Poly1305 with the 32 byte one-time key r || s, on backend B.
+    pub fn with_backend(key: &[u8; 32]) -> Self {
+        let r0 = u64::from_le_bytes(key[0..8].try_into().expect("len 8")) & POLY1305_R_CLAMP_LO;
+        let r1 = u64::from_le_bytes(key[8..16].try_into().expect("len 8")) & POLY1305_R_CLAMP_HI;
 
         Self {
-            secret_r: (r0, r1),
-            secret_s: (s0, s1),
-            accum: (0, 0, 0),
+            backend: B::new(r0 as u128 | (r1 as u128) << 64),
+            secret_s: u128::from_le_bytes(key[16..].try_into().expect("len 16")),
+            buffer: [0; POLY1305_MSG_CHUNK_SIZE_BYTES],
+            buflen: 0,
         }
     }
 }
 
-impl OneTimeAuthenticator for Poly1305 {
-    fn update(&mut self, message: &[u8]) {
-        let (mut h0, mut h1, mut h2) = self.accum;
+impl<B: Poly1305Backend> OneTimeAuthenticator for Poly1305<B> {
+    fn update(&mut self, mut message: &[u8]) {
+        let fill = POLY1305_MSG_CHUNK_SIZE_BYTES - self.buflen;
 
-        for window in message.chunks(POLY1305_MSG_CHUNK_SIZE_BYTES) {
-            let mut owned_w: Vec<u8> = window.to_vec();
-
-            // step 0.5: resize to chunksize and add a bit above the message
-            if owned_w.len() < POLY1305_MSG_CHUNK_SIZE_BYTES {
-                owned_w.push(1);
-                owned_w.resize(POLY1305_MSG_CHUNK_SIZE_BYTES, 0);
+        if self.buflen > 0 {
+            if message.len() < fill {
+                self.buffer[self.buflen..self.buflen + message.len()].copy_from_slice(message);
+                self.buflen += message.len();
+                return;
             }
+            self.buffer[self.buflen..].copy_from_slice(&message[..fill]);
+            message = &message[fill..];
+            let block = self.buffer;
+            self.backend.block(&block, true);
+            self.buflen = 0;
+        }
 
-            let mut c = 0u64;
-            // step 1: h (the accumulator) + m
-            (h0, c) = arith::carrying_add(
-                h0,
-                u64::from_le_bytes(owned_w[0..8].try_into().expect("Good!")),
-                0,
-            );
-            (h1, c) = arith::carrying_add(
-                h1,
-                u64::from_le_bytes(owned_w[8..16].try_into().expect("Good!")),
-                c,
-            );
+        // whole blocks straight from the input, the rest waits in the buffer
+        let whole = message.len() - message.len() % POLY1305_MSG_CHUNK_SIZE_BYTES;
+        self.backend.blocks(&message[..whole]);
+        let rest = &message[whole..];
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buflen = rest.len();
+    }
 
-            // step 0.5: set a bit above the message size
-            if window.len() == POLY1305_MSG_CHUNK_SIZE_BYTES {
-                h2 = c + 1; // if len is chunk size, adding 1 to the 2^128 limb is fine
-            }
+    fn finalize(mut self) -> Vec<u8> {
+        // the short final block gets a 1 byte instead of the bit at 2^128
+        if self.buflen > 0 {
+            self.buffer[self.buflen] = 1;
+            self.buffer[self.buflen + 1..].fill(0);
+            let block = self.buffer;
+            self.backend.block(&block, false);
+        }
+
+        let tag = self.backend.finalize().wrapping_add(self.secret_s);
+        tag.to_le_bytes().to_vec()
+    }
+}
+
+/// The Poly1305 tag of message under the one-time key r || s.
+pub fn poly1305(key: &[u8; 32], message: &[u8]) -> [u8; 16] {
+    let mut mac = Poly1305::new(key);
+    mac.update(message);
+    mac.finalize().try_into().expect("len 16")
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, RngCore};
+
+    use crate::{
+        mac::{
+            MacError, OneTimeAuthenticator, Poly1305, Poly1305Backend, Poly1305Radix26,
+            Poly1305Radix44, Poly1305Radix44x4, Poly1305Radix64, poly1305,
+        },
+        slice::{decode_hex, u8_to_hexstr},
+    };
 
-            // step 2: h * r (long mul)
-            //
-            //    h0    h1    h2
-            //    r1    r2         x
-            // =========================
-            //   h0r0  h1r0  h2r0               <--- 128 bit products
-            //         h0r1  h1r1  h2r1   +     <--|
-            // ==============================
-            //    t0    t1    t2    t3          <--- 128 bit intermediates with
-            //                                       overlapping limbs (!)
-            // CARRYING THE INTERMEDIATE LIMBS
-            // ===============================
-            //         t0.1  t1.1  t2.1         <--- t3 does not have a higher half (h2r1)
-            //   t0.0  t1.0  t2.0  t3.0        +
-            // =====================================
-            //   res0  res1  res2  res3
-            //
-            // having r clamped means that no overflow can occur while adding
-            // to the intermediates t1 and t2.
-            //
-            // because h2 is capped to 5, and r has its first 4 bits clamped,
-            // h2r0 and h2r1 do not have a higher half, meaning there is no
-            // result limb res4.
-            //
-            // takeaways and optimisations:
-            //  - overflow cannot occur while calculating t* intermediates,
-            //  - there isnt a result limb res4 (h2r1/t3 higher half), and
-            //  - t2's higher half is equal to h1r1's higher half.
-
-            let h0r0 = arith::widening_mul(h0, self.secret_r.0);
-            let h1r0 = arith::widening_mul(h1, self.secret_r.0);
-            let h2r0 = h2 * self.secret_r.0; // h2 is <= 5, r is clamped
-            let h0r1 = arith::widening_mul(h0, self.secret_r.1);
-            let h1r1 = arith::widening_mul(h1, self.secret_r.1);
-            let h2r1 = h2 * self.secret_r.1; // h2 is <= 5, r is clamped
-
-            // intermediates
-            let (t1lo, mut c) = arith::carrying_add(h1r0.0, h0r1.0, 0);
-            let t1hi = arith::carrying_add(h1r0.1, h0r1.1, c).0;
-            let (t2lo, mut c) = arith::carrying_add(h2r0, h1r1.0, 0);
-            let t2hi = arith::carrying_add(h2r0, h1r1.0, c).0;
-
-            // results
-            let res0 = h0r0.0;
-            let (res1, mut c) = arith::carrying_add(h0r0.1, t1lo, 0);
-            let (res2, mut c) = arith::carrying_add(t1hi, t2lo, c);
-            let (res3, mut c) = arith::carrying_add(t2hi, h2r1, c);
-
-            // step 3: reducing the result mod 2^130 - 5
-            // this is the same (?) as working with Curve25519 using Solinas primes
-            // c * 2^130 + n = c * 5 + n (mod 2^130 - 5)
-
-            // split the result into below and above 2^130 (carry)
-            // the carry is c, below is n.
-            let lower_2_mask = 0b11;
-            let n2 = res2 & lower_2_mask;
-            let mut carry = (res2 & !lower_2_mask, res3); // this is actually 4c
-
-            // add 4c to h
-            (h0, c) = arith::carrying_add(res0, carry.0, 0);
-            (h1, c) = arith::carrying_add(res1, carry.1, c);
-            h2 = n2 + c;
-
-            // calculate c from 4c
-            carry.0 = carry.0 >> 2 | carry.1 << 62;
-            carry.1 = carry.1 >> 2;
-
-            // add the last c to h
-            (h0, c) = arith::carrying_add(res0, carry.0, 0);
-            (h1, c) = arith::carrying_add(res1, carry.1, c);
-            h2 += c;
+    fn key(hex: &str) -> [u8; 32] {
+        decode_hex(hex).try_into().unwrap()
+    }
+
+    fn tag<B: Poly1305Backend>(key: &[u8; 32], message: &[u8]) -> Vec<u8> {
+        let mut mac = Poly1305::<B>::with_backend(key);
+        mac.update(message);
+        mac.finalize()
+    }
+
+    fn all_backends(key: &[u8; 32], message: &[u8]) -> [Vec<u8>; 4] {
+        [
+            tag::<Poly1305Radix64>(key, message),
+            tag::<Poly1305Radix26>(key, message),
+            tag::<Poly1305Radix44>(key, message),
+            tag::<Poly1305Radix44x4>(key, message),
+        ]
+    }
+
+    #[test]
+    fn poly1305_rfc8439() {
+        // section 2.5.2
+        let key1 = key("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b");
+        let res1 = u8_to_hexstr(&poly1305(&key1, b"Cryptographic Forum Research Group"));
+        let exp1 = "a8061dc1305136c6c22b8baf0c0127a9";
+        assert_eq!(res1, exp1);
+    }
+
+    #[test]
+    fn poly1305_rfc8439_appendix() {
+        // appendix A.3
+        let ietf = b"Any submission to the IETF intended by the Contributor for publication as all or part of an IETF Internet-Draft or RFC and any statement made within the context of an IETF activity is considered an \"IETF Contribution\". Such statements include oral statements in IETF sessions, as well as written and electronic communications made at any time or place, which are addressed to";
+        let jabberwocky = b"'Twas brillig, and the slithy toves\nDid gyre and gimble in the wabe:\nAll mimsy were the borogoves,\nAnd the mome raths outgrabe.";
+        let r1 = "01000000000000000000000000000000";
+        let r2 = "02000000000000000000000000000000";
+        let r10 = "01000000000000000400000000000000";
+        let zero = "00000000000000000000000000000000";
+        let ones = "ffffffffffffffffffffffffffffffff";
+        let m10 = "e33594d7505e43b900000000000000003394d7505e4379cd010000000000000000000000000000000000000000000000";
+
+        let cases: [(String, Vec<u8>, &str); 11] = [
+            (zero.repeat(2), vec![0; 64], zero),
+            (
+                format!("{zero}36e5f6b5c5e06070f0efca96227a863e"),
+                ietf.to_vec(),
+                "36e5f6b5c5e06070f0efca96227a863e",
+            ),
+            (
+                format!("36e5f6b5c5e06070f0efca96227a863e{zero}"),
+                ietf.to_vec(),
+                "f3477e7cd95417af89a6b8794c310cf0",
+            ),
+            (
+                "1c9240a5eb55d38af333888604f6b5f0473917c1402b80099dca5cbc207075c0".to_string(),
+                jabberwocky.to_vec(),
+                "4541669a7eaaee61e708dc7cbcc5eb62",
+            ),
+            // the rest check the carries and the final reduction
+            (
+                format!("{r2}{zero}"),
+                decode_hex(ones),
+                "03000000000000000000000000000000",
+            ),
+            (
+                format!("{r2}{ones}"),
+                decode_hex(r2),
+                "03000000000000000000000000000000",
+            ),
+            (
+                format!("{r1}{zero}"),
+                decode_hex(&format!(
+                    "{ones}f0ffffffffffffffffffffffffffffff11000000000000000000000000000000"
+                )),
+                "05000000000000000000000000000000",
+            ),
+            (
+                format!("{r1}{zero}"),
+                decode_hex(&format!(
+                    "{ones}fbfefefefefefefefefefefefefefefe01010101010101010101010101010101"
+                )),
+                zero,
+            ),
+            (
+                format!("{r2}{zero}"),
+                decode_hex("fdffffffffffffffffffffffffffffff"),
+                "faffffffffffffffffffffffffffffff",
+            ),
+            (
+                format!("{r10}{zero}"),
+                decode_hex(&format!("{m10}01000000000000000000000000000000")),
+                "14000000000000005500000000000000",
+            ),
+            (
+                format!("{r10}{zero}"),
+                decode_hex(m10),
+                "13000000000000000000000000000000",
+            ),
+        ];
+
+        for (key_hex, message, expected) in cases {
+            for res in all_backends(&key(&key_hex), &message) {
+                assert_eq!(u8_to_hexstr(&res), expected);
+            }
         }
+    }
 
-        self.accum = (h0, h1, h2);
+    #[test]
+    fn poly1305_verify() {
+        let key1 = key("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b");
+        let mut tag = decode_hex("a8061dc1305136c6c22b8baf0c0127a9");
+
+        let mut mac = Poly1305::new(&key1);
+        mac.update(b"Cryptographic Forum Research Group");
+        assert_eq!(mac.verify(&tag), Ok(()));
+
+        tag[0] ^= 1;
+        let mut mac = Poly1305::new(&key1);
+        mac.update(b"Cryptographic Forum Research Group");
+        assert_eq!(mac.verify(&tag), Err(MacError));
+
+        let mut mac = Poly1305::new(&key1);
+        mac.update(b"Cryptographic Forum Research Group");
+        assert_eq!(mac.verify(&tag[..15]), Err(MacError));
     }
 
-    fn finalize(self) -> Vec<u8> {
-        // fully reduce the accumulator after the partial reduction in update()
-        //  - right now, 0 < h < 2 * (2^130 - 5)
-        // reduce h by subtracting 2^130 - 5
-        let (mut h0, mut h1, mut h2) = self.accum;
-        let (t0, b) = arith::borrowing_sub(h0, POLY1305_MOD_P.0, 0);
-        let (t1, b) = arith::borrowing_sub(h1, POLY1305_MOD_P.1, b);
-        let (t2, b) = arith::borrowing_sub(h2, POLY1305_MOD_P.2, b);
-
-        h0 = arith::ct_select_64(h0, t0, b);
-        h1 = arith::ct_select_64(h1, t1, b);
-
-        // compute tag
-        let mut carry = 0u64;
-        (h0, carry) = arith::carrying_add(h0, self.secret_s.0, carry);
-        h1 = arith::carrying_add(h0, self.secret_s.1, carry).0;
-
-        let mut res = vec![0u8; 16];
-        res[..8].copy_from_slice(&h0.to_le_bytes());
-        res[8..].copy_from_slice(&h1.to_le_bytes());
-        res
+    #[test]
+    fn poly1305_split_updates() {
+        let mut rng = rand::rng();
+        for _ in 0..200 {
+            let mut key = [0; 32];
+            rng.fill_bytes(&mut key);
+            let mut message = vec![0; rng.random_range(0..200)];
+            rng.fill_bytes(&mut message);
+            let expected = poly1305(&key, &message);
+
+            // feed the same message in random pieces, empty ones included
+            let mut mac = Poly1305::new(&key);
+            let mut rest = &message[..];
+            let mut pieces = Vec::new();
+            while !rest.is_empty() {
+                let (piece, tail) = rest.split_at(rng.random_range(0..=rest.len().min(40)));
+                mac.update(piece);
+                pieces.push(piece.len());
+                rest = tail;
+            }
+            assert_eq!(
+                mac.finalize(),
+                expected,
+                "key {} message {} pieces {pieces:?}",
+                u8_to_hexstr(&key),
+                u8_to_hexstr(&message)
+            );
+        }
     }
 
-    fn verify(self, tag: &[u8]) {
-        todo!()
+    #[test]
+    fn poly1305_backends_agree() {
+        let mut rng = rand::rng();
+        for i in 0..300 {
+            let mut key = [0; 32];
+            let mut message = vec![0; rng.random_range(0..300)];
+            // all ones first, for the largest limbs and carries
+            if i > 0 {
+                rng.fill_bytes(&mut key);
+                rng.fill_bytes(&mut message);
+            } else {
+                key.fill(0xff);
+                message.fill(0xff);
+            }
+
+            let [expected, rest @ ..] = all_backends(&key, &message);
+            for res in rest {
+                assert_eq!(
+                    res,
+                    expected,
+                    "key {} message {}",
+                    u8_to_hexstr(&key),
+                    u8_to_hexstr(&message)
+                );
+            }
+        }
     }
 }
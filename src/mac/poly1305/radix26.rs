@@ -0,0 +1,104 @@
+use super::Poly1305Backend;
+
+const MASK26: u32 = (1 << 26) - 1;
+
+/// h in five 26 bit limbs, so that every product fits a u64 and only 32 bit
+/// multiplies are needed. For 32 bit targets.
+#[derive(Clone)]
+pub struct Poly1305Radix26 {
+    r: [u32; 5],
+    // r[1..] * 5, for the products that wrap around 2^130
+    s: [u32; 4],
+    h: [u32; 5],
+}
+
+impl Poly1305Backend for Poly1305Radix26 {
+    fn new(r: u128) -> Self {
+        // r is clamped, so the top limb has 20 bits and all of them fit
+        let r: [u32; 5] = std::array::from_fn(|i| (r >> (26 * i)) as u32 & MASK26);
+        Self {
+            r,
+            s: [r[1] * 5, r[2] * 5, r[3] * 5, r[4] * 5],
+            h: [0; 5],
+        }
+    }
+
+    fn block(&mut self, block: &[u8; 16], hibit: bool) {
+        let t: [u32; 4] = std::array::from_fn(|i| {
+            u32::from_le_bytes(block[4 * i..4 * i + 4].try_into().expect("len 4"))
+        });
+
+        // h += m, with the bit above the message at 2^128 = 2^24 in limb 4
+        let h0 = (self.h[0] + (t[0] & MASK26)) as u64;
+        let h1 = (self.h[1] + ((t[0] >> 26 | t[1] << 6) & MASK26)) as u64;
+        let h2 = (self.h[2] + ((t[1] >> 20 | t[2] << 12) & MASK26)) as u64;
+        let h3 = (self.h[3] + ((t[2] >> 14 | t[3] << 18) & MASK26)) as u64;
+        let h4 = (self.h[4] + (t[3] >> 8 | (hibit as u32) << 24)) as u64;
+
+        // h *= r, the limbs at 2^130 and above come back multiplied by 5
+        let [r0, r1, r2, r3, r4] = self.r.map(u64::from);
+        let [s1, s2, s3, s4] = self.s.map(u64::from);
+        let d0 = h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1;
+        let mut d1 = h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2;
+        let mut d2 = h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3;
+        let mut d3 = h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4;
+        let mut d4 = h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0;
+
+        // partial reduction, limb 1 may keep a bit above 26
+        d1 += d0 >> 26;
+        d2 += d1 >> 26;
+        d3 += d2 >> 26;
+        d4 += d3 >> 26;
+        let h0 = (d0 & MASK26 as u64) + (d4 >> 26) * 5;
+        self.h = [
+            h0 as u32 & MASK26,
+            (d1 & MASK26 as u64) as u32 + (h0 >> 26) as u32,
+            (d2 & MASK26 as u64) as u32,
+            (d3 & MASK26 as u64) as u32,
+            (d4 & MASK26 as u64) as u32,
+        ];
+    }
+
+    fn finalize(self) -> u128 {
+        let [mut h0, mut h1, mut h2, mut h3, mut h4] = self.h;
+
+        // full carry, h < 2^130 + something small
+        h2 += h1 >> 26;
+        h1 &= MASK26;
+        h3 += h2 >> 26;
+        h2 &= MASK26;
+        h4 += h3 >> 26;
+        h3 &= MASK26;
+        h0 += (h4 >> 26) * 5;
+        h4 &= MASK26;
+        h1 += h0 >> 26;
+        h0 &= MASK26;
+
+        // g = h + 5 - 2^130, which is h - p if that does not go negative
+        let mut g0 = h0 + 5;
+        let mut g1 = h1 + (g0 >> 26);
+        g0 &= MASK26;
+        let mut g2 = h2 + (g1 >> 26);
+        g1 &= MASK26;
+        let mut g3 = h3 + (g2 >> 26);
+        g2 &= MASK26;
+        let g4 = (h4 + (g3 >> 26)).wrapping_sub(1 << 26);
+        g3 &= MASK26;
+
+        // the sign bit of g4 is set when h < p, then keep h
+        let mask = (g4 >> 31).wrapping_sub(1);
+        let select = |h: u32, g: u32| (h & !mask) | (g & mask);
+        let limbs = [
+            select(h0, g0),
+            select(h1, g1),
+            select(h2, g2),
+            select(h3, g3),
+            select(h4, g4),
+        ];
+
+        limbs
+            .iter()
+            .enumerate()
+            .fold(0u128, |acc, (i, &limb)| acc | (limb as u128) << (26 * i))
+    }
+}
@@ -0,0 +1,164 @@
+use super::Poly1305Backend;
+
+const MASK44: u64 = (1 << 44) - 1;
+const MASK42: u64 = (1 << 42) - 1;
+
+// 44 + 44 + 42 bit limbs, the last one ends at 2^130
+type Limbs = [u64; 3];
+
+fn load(block: &[u8; 16], hibit: bool) -> Limbs {
+    let t0 = u64::from_le_bytes(block[..8].try_into().expect("len 8"));
+    let t1 = u64::from_le_bytes(block[8..].try_into().expect("len 8"));
+    [
+        t0 & MASK44,
+        (t0 >> 44 | t1 << 20) & MASK44,
+        t1 >> 24 | (hibit as u64) << 40,
+    ]
+}
+
+fn add(a: Limbs, b: Limbs) -> Limbs {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+/// The unreduced product h * r. The parts at 2^132 and above wrap around as
+/// 2^132 = 4 * 2^130 = 4 * 5 (mod 2^130 - 5).
+fn mul(h: Limbs, r: Limbs) -> [u128; 3] {
+    let [h0, h1, h2] = h.map(u128::from);
+    let [r0, r1, r2] = r.map(u128::from);
+    let (s1, s2) = (r1 * 20, r2 * 20);
+    [
+        h0 * r0 + h1 * s2 + h2 * s1,
+        h0 * r1 + h1 * r0 + h2 * s2,
+        h0 * r2 + h1 * r1 + h2 * r0,
+    ]
+}
+
+/// Carries a product back into limbs, limb 1 may keep a bit above 44.
+fn reduce(d: [u128; 3]) -> Limbs {
+    let d1 = d[1] + (d[0] >> 44);
+    let d2 = d[2] + (d1 >> 44);
+    let h0 = (d[0] as u64 & MASK44) + (d2 >> 42) as u64 * 5;
+    [
+        h0 & MASK44,
+        (d1 as u64 & MASK44) + (h0 >> 44),
+        d2 as u64 & MASK42,
+    ]
+}
+
+fn split_r(r: u128) -> Limbs {
+    [
+        r as u64 & MASK44,
+        (r >> 44) as u64 & MASK44,
+        (r >> 88) as u64,
+    ]
+}
+
+/// h fully reduced mod 2^130 - 5, cut to 128 bits.
+fn freeze(h: Limbs) -> u128 {
+    let [mut h0, mut h1, mut h2] = h;
+
+    // full carry, twice as the first one can push h2 over 2^42 again
+    for _ in 0..2 {
+        h2 += h1 >> 44;
+        h1 &= MASK44;
+        h0 += (h2 >> 42) * 5;
+        h2 &= MASK42;
+        h1 += h0 >> 44;
+        h0 &= MASK44;
+    }
+
+    // g = h + 5 - 2^130, which is h - p if that does not go negative
+    let mut g0 = h0 + 5;
+    let mut g1 = h1 + (g0 >> 44);
+    g0 &= MASK44;
+    let g2 = (h2 + (g1 >> 44)).wrapping_sub(1 << 42);
+    g1 &= MASK44;
+
+    // the sign bit of g2 is set when h < p, then keep h
+    let mask = (g2 >> 63).wrapping_sub(1);
+    let h0 = (h0 & !mask) | (g0 & mask);
+    let h1 = (h1 & !mask) | (g1 & mask);
+    let h2 = (h2 & !mask) | (g2 & mask);
+    h0 as u128 | (h1 as u128) << 44 | (h2 as u128) << 88
+}
+
+/// h in three 44 bit limbs, with u128 products. Fewer multiplies than the
+/// radix 2^26 backend, and simpler carries than the radix 2^64 one.
+#[derive(Clone)]
+pub struct Poly1305Radix44 {
+    r: Limbs,
+    h: Limbs,
+}
+
+impl Poly1305Backend for Poly1305Radix44 {
+    fn new(r: u128) -> Self {
+        Self {
+            r: split_r(r),
+            h: [0; 3],
+        }
+    }
+
+    fn block(&mut self, block: &[u8; 16], hibit: bool) {
+        self.h = reduce(mul(add(self.h, load(block, hibit)), self.r));
+    }
+
+    fn finalize(self) -> u128 {
+        freeze(self.h)
+    }
+}
+
+/// The radix 2^44 backend, taking four blocks at a time:
+/// h = (h + m0) r^4 + m1 r^3 + m2 r^2 + m3 r, with a single reduction. The
+/// four products are independent, so they can run in parallel.
+#[derive(Clone)]
+pub struct Poly1305Radix44x4 {
+    // r, r^2, r^3, r^4
+    powers: [Limbs; 4],
+    h: Limbs,
+}
+
+impl Poly1305Backend for Poly1305Radix44x4 {
+    fn new(r: u128) -> Self {
+        let r = split_r(r);
+        let r2 = reduce(mul(r, r));
+        let r3 = reduce(mul(r2, r));
+        let r4 = reduce(mul(r2, r2));
+        Self {
+            powers: [r, r2, r3, r4],
+            h: [0; 3],
+        }
+    }
+
+    fn block(&mut self, block: &[u8; 16], hibit: bool) {
+        self.h = reduce(mul(add(self.h, load(block, hibit)), self.powers[0]));
+    }
+
+    fn blocks(&mut self, blocks: &[u8]) {
+        let mut chunks = blocks.chunks_exact(64);
+        for chunk in &mut chunks {
+            let m: [Limbs; 4] = std::array::from_fn(|i| {
+                load(
+                    chunk[16 * i..16 * (i + 1)].try_into().expect("len 16"),
+                    true,
+                )
+            });
+
+            let mut d = mul(add(self.h, m[0]), self.powers[3]);
+            for (m, power) in m[1..].iter().zip(self.powers[..3].iter().rev()) {
+                let product = mul(*m, *power);
+                for (a, b) in d.iter_mut().zip(product) {
+                    *a += b;
+                }
+            }
+            self.h = reduce(d);
+        }
+
+        for block in chunks.remainder().chunks_exact(16) {
+            self.block(block.try_into().expect("len 16"), true);
+        }
+    }
+
+    fn finalize(self) -> u128 {
+        freeze(self.h)
+    }
+}
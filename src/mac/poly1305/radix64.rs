@@ -0,0 +1,130 @@
+use super::Poly1305Backend;
+use crate::arith;
+
+const POLY1305_MOD_P: (u64, u64, u64) = (0xffff_ffff_ffff_fffb, 0xffff_ffff_ffff_ffff, 0x3);
+
+/// h in two 64 bit limbs and a few bits above, multiplied with 64 bit
+/// widening multiplies. The fastest on 64 bit targets.
+#[derive(Clone)]
+pub struct Poly1305Radix64 {
+    secret_r: (u64, u64),   // lo, hi
+    accum: (u64, u64, u64), // split into lo, mid, hi -> can exceed
+}
+
+impl Poly1305Backend for Poly1305Radix64 {
+    fn new(r: u128) -> Self {
+        Self {
+            secret_r: (r as u64, (r >> 64) as u64),
+            accum: (0, 0, 0),
+        }
+    }
+
+    fn block(&mut self, block: &[u8; 16], hibit: bool) {
+        let (mut h0, mut h1, mut h2) = self.accum;
+
+        let mut c = 0u64;
+        // step 1: h (the accumulator) + m
+        (h0, c) = arith::carrying_add(
+            h0,
+            u64::from_le_bytes(block[0..8].try_into().expect("len 8")),
+            0,
+        );
+        (h1, c) = arith::carrying_add(
+            h1,
+            u64::from_le_bytes(block[8..16].try_into().expect("len 8")),
+            c,
+        );
+
+        // step 1.5: set the bit above the message, for a short final block
+        // it was already set in the padding
+        h2 += c + hibit as u64;
+
+        // step 2: h * r (long mul)
+        //
+        //    h0    h1    h2
+        //    r1    r2         x
+        // =========================
+        //   h0r0  h1r0  h2r0               <--- 128 bit products
+        //         h0r1  h1r1  h2r1   +     <--|
+        // ==============================
+        //    t0    t1    t2    t3          <--- 128 bit intermediates with
+        //                                       overlapping limbs (!)
+        // CARRYING THE INTERMEDIATE LIMBS
+        // ===============================
+        //         t0.1  t1.1  t2.1         <--- t3 does not have a higher half (h2r1)
+        //   t0.0  t1.0  t2.0  t3.0        +
+        // =====================================
+        //   res0  res1  res2  res3
+        //
+        // having r clamped means that no overflow can occur while adding
+        // to the intermediates t1 and t2.
+        //
+        // because h2 is capped to 5, and r has its first 4 bits clamped,
+        // h2r0 and h2r1 do not have a higher half, meaning there is no
+        // result limb res4.
+        //
+        // takeaways and optimisations:
+        //  - overflow cannot occur while calculating t* intermediates,
+        //  - there isnt a result limb res4 (h2r1/t3 higher half), and
+        //  - t2's higher half is equal to h1r1's higher half.
+
+        let h0r0 = arith::widening_mul(h0, self.secret_r.0);
+        let h1r0 = arith::widening_mul(h1, self.secret_r.0);
+        let h2r0 = h2 * self.secret_r.0; // h2 is <= 5, r is clamped
+        let h0r1 = arith::widening_mul(h0, self.secret_r.1);
+        let h1r1 = arith::widening_mul(h1, self.secret_r.1);
+        let h2r1 = h2 * self.secret_r.1; // h2 is <= 5, r is clamped
+
+        // intermediates
+        let (t1lo, mut c) = arith::carrying_add(h1r0.0, h0r1.0, 0);
+        let t1hi = arith::carrying_add(h1r0.1, h0r1.1, c).0;
+        let (t2lo, mut c) = arith::carrying_add(h2r0, h1r1.0, 0);
+        let t2hi = h1r1.1 + c;
+
+        // results
+        let res0 = h0r0.0;
+        let (res1, mut c) = arith::carrying_add(h0r0.1, t1lo, 0);
+        let (res2, mut c) = arith::carrying_add(t1hi, t2lo, c);
+        let (res3, mut c) = arith::carrying_add(t2hi, h2r1, c);
+
+        // step 3: reducing the result mod 2^130 - 5
+        // this is the same (?) as working with Curve25519 using Solinas primes
+        // c * 2^130 + n = c * 5 + n (mod 2^130 - 5)
+
+        // split the result into below and above 2^130 (carry)
+        // the carry is c, below is n.
+        let lower_2_mask = 0b11;
+        let n2 = res2 & lower_2_mask;
+        let mut carry = (res2 & !lower_2_mask, res3); // this is actually 4c
+
+        // add 4c to h
+        (h0, c) = arith::carrying_add(res0, carry.0, 0);
+        (h1, c) = arith::carrying_add(res1, carry.1, c);
+        h2 = n2 + c;
+
+        // calculate c from 4c
+        carry.0 = carry.0 >> 2 | carry.1 << 62;
+        carry.1 = carry.1 >> 2;
+
+        // add the last c to h
+        (h0, c) = arith::carrying_add(h0, carry.0, 0);
+        (h1, c) = arith::carrying_add(h1, carry.1, c);
+        h2 += c;
+
+        self.accum = (h0, h1, h2);
+    }
+
+    fn finalize(self) -> u128 {
+        // fully reduce the accumulator after the partial reduction in block()
+        //  - right now, 0 < h < 2 * (2^130 - 5)
+        // reduce h by subtracting 2^130 - 5
+        let (h0, h1, h2) = self.accum;
+        let (t0, b) = arith::borrowing_sub(h0, POLY1305_MOD_P.0, 0);
+        let (t1, b) = arith::borrowing_sub(h1, POLY1305_MOD_P.1, b);
+        let (_, b) = arith::borrowing_sub(h2, POLY1305_MOD_P.2, b);
+
+        let h0 = arith::ct_select_64(h0, t0, b);
+        let h1 = arith::ct_select_64(h1, t1, b);
+        h0 as u128 | (h1 as u128) << 64
+    }
+}
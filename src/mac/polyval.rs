@@ -0,0 +1,192 @@
+use crate::mac::OneTimeAuthenticator;
+
+const POLYVAL_BLOCK_BYTES: usize = 16;
+
+/// Carry-less multiply of x and y, the low 64 bits. The bits are spread out
+/// so that every fourth one is set, then the carries of the integer
+/// multiplies land in the holes and are masked away (BearSSL's bmul64).
+/// There are no table lookups or branches on the data.
+fn bmul64(x: u64, y: u64) -> u64 {
+    const MASKS: [u64; 4] = [
+        0x1111_1111_1111_1111,
+        0x2222_2222_2222_2222,
+        0x4444_4444_4444_4444,
+        0x8888_8888_8888_8888,
+    ];
+    let x = MASKS.map(|m| x & m);
+    let y = MASKS.map(|m| y & m);
+
+    let mut z = 0;
+    for (i, mask) in MASKS.iter().enumerate() {
+        // all pairs whose hole positions add up to i (mod 4)
+        let zi = (0..4).fold(0, |acc, j| acc ^ x[j].wrapping_mul(y[(4 + i - j) % 4]));
+        z |= zi & mask;
+    }
+    z
+}
+
+/// Carry-less multiply of x and y, the full 128 bit result. The high half is
+/// the low half of the product of the bit reversed inputs, reversed back.
+fn clmul(x: u64, y: u64) -> (u64, u64) {
+    let lo = bmul64(x, y);
+    let hi = bmul64(x.reverse_bits(), y.reverse_bits()).reverse_bits() >> 1;
+    (lo, hi)
+}
+
+/// a * b * x^-128 in POLYVAL's field, GF(2^128) mod
+/// x^128 + x^127 + x^126 + x^121 + 1, with the bits in little endian order.
+/// Karatsuba for the 256 bit product, then a Montgomery reduction.
+fn polyval_mul(a: u128, b: u128) -> u128 {
+    let (a0, a1) = (a as u64, (a >> 64) as u64);
+    let (b0, b1) = (b as u64, (b >> 64) as u64);
+
+    let (z0lo, z0hi) = clmul(a0, b0);
+    let (z1lo, z1hi) = clmul(a1, b1);
+    let (mut z2lo, mut z2hi) = clmul(a0 ^ a1, b0 ^ b1);
+    z2lo ^= z0lo ^ z1lo;
+    z2hi ^= z0hi ^ z1hi;
+
+    // the product in four words, v0 lowest
+    let v0 = z0lo;
+    let mut v1 = z0hi ^ z2lo;
+    let mut v2 = z1lo ^ z2hi;
+    let mut v3 = z1hi;
+
+    // fold the low 128 bits into the high ones, 64 at a time
+    v2 ^= v0 ^ (v0 >> 1) ^ (v0 >> 2) ^ (v0 >> 7);
+    v1 ^= (v0 << 63) ^ (v0 << 62) ^ (v0 << 57);
+    v3 ^= v1 ^ (v1 >> 1) ^ (v1 >> 2) ^ (v1 >> 7);
+    v2 ^= (v1 << 63) ^ (v1 << 62) ^ (v1 << 57);
+
+    v2 as u128 | (v3 as u128) << 64
+}
+
+/// The POLYVAL universal hash from AES-GCM-SIV (RFC 8452). Like Poly1305, a
+/// key must only authenticate one message. A final partial block is padded
+/// with zeros.
+#[derive(Clone)]
+pub struct Polyval {
+    h: u128,
+    accum: u128,
+    buffer: [u8; POLYVAL_BLOCK_BYTES],
+    buflen: usize,
+}
+
+impl Polyval {
+    /// POLYVAL with the 16 byte key H.
+    pub fn new(key: &[u8; 16]) -> Self {
+        Self::from_element(u128::from_le_bytes(*key))
+    }
+
+    pub(super) fn from_element(h: u128) -> Self {
+        Self {
+            h,
+            accum: 0,
+            buffer: [0; POLYVAL_BLOCK_BYTES],
+            buflen: 0,
+        }
+    }
+
+    /// S = (S + X) * H, with X already read as a field element.
+    pub(super) fn block(&mut self, x: u128) {
+        self.accum = polyval_mul(self.accum ^ x, self.h);
+    }
+
+    /// The accumulator after the last block, with the padded partial block
+    /// read by f.
+    pub(super) fn finish(mut self, f: fn(&[u8; 16]) -> u128) -> u128 {
+        if self.buflen > 0 {
+            self.buffer[self.buflen..].fill(0);
+            let block = f(&self.buffer);
+            self.block(block);
+        }
+        self.accum
+    }
+
+    /// Buffers message into blocks for f, like OneTimeAuthenticator::update.
+    pub(super) fn update_with(&mut self, mut message: &[u8], f: fn(&[u8; 16]) -> u128) {
+        if self.buflen > 0 {
+            let fill = (POLYVAL_BLOCK_BYTES - self.buflen).min(message.len());
+            self.buffer[self.buflen..self.buflen + fill].copy_from_slice(&message[..fill]);
+            self.buflen += fill;
+            message = &message[fill..];
+            if self.buflen < POLYVAL_BLOCK_BYTES {
+                return;
+            }
+            let block = f(&self.buffer);
+            self.block(block);
+            self.buflen = 0;
+        }
+
+        let mut blocks = message.chunks_exact(POLYVAL_BLOCK_BYTES);
+        for block in &mut blocks {
+            self.block(f(block.try_into().expect("len 16")));
+        }
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buflen = rest.len();
+    }
+}
+
+impl OneTimeAuthenticator for Polyval {
+    fn update(&mut self, message: &[u8]) {
+        self.update_with(message, |block| u128::from_le_bytes(*block));
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.finish(|block| u128::from_le_bytes(*block))
+            .to_le_bytes()
+            .to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mac::{MacError, OneTimeAuthenticator, Polyval, polyval::bmul64},
+        slice::{decode_hex, u8_to_hexstr},
+    };
+
+    fn polyval(key: &str, message: &[u8]) -> String {
+        let mut mac = Polyval::new(&decode_hex(key).try_into().unwrap());
+        mac.update(message);
+        u8_to_hexstr(&mac.finalize())
+    }
+
+    #[test]
+    fn basic_bmul64() {
+        // (x + 1)^2 = x^2 + 1 without carries
+        assert_eq!(bmul64(3, 3), 5);
+        assert_eq!(bmul64(0xff, 0xff), 0x5555);
+        assert_eq!(bmul64(1 << 63, 2), 0);
+        assert_eq!(bmul64(u64::MAX, 1), u64::MAX);
+    }
+
+    #[test]
+    fn polyval_rfc8452() {
+        // appendix A
+        let key = "25629347589242761d31f826ba4b757b";
+        let message =
+            decode_hex("4f4f95668c83dfb6401762bb2d01a262d1a24ddd2721d006bbe45f20d3c9f362");
+        assert_eq!(polyval(key, &message), "f7a3b47b846119fae5b7866cf5e5b77e");
+    }
+
+    #[test]
+    fn polyval_partial_blocks() {
+        // from the polyval crate, padded with zeros
+        let message: Vec<u8> = (0..100).collect();
+        let res1 = polyval("ffffffffffffffffffffffffffffffff", &message);
+        assert_eq!(res1, "c8efaff0476073b66ef0deccb1d08824");
+        let res2 = polyval("25629347589242761d31f826ba4b757b", &message[..33]);
+        assert_eq!(res2, "145964ea9e3f531d2cdd46e542ec060c");
+
+        let mut mac = Polyval::new(&[0xff; 16]);
+        for piece in message.chunks(7) {
+            mac.update(piece);
+        }
+        let tag = decode_hex("c8efaff0476073b66ef0deccb1d08824");
+        assert_eq!(mac.clone().verify(&tag), Ok(()));
+        mac.update(b"!");
+        assert_eq!(mac.verify(&tag), Err(MacError));
+    }
+}
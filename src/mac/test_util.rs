@@ -0,0 +1,11 @@
+use crate::mac::Mac;
+
+/// Computes the tag through the generic `Mac` interface, after resetting
+/// away an earlier message. Works for any MAC that can reuse its key.
+pub(crate) fn tag<M: Mac>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = M::new_from_key(key);
+    mac.update(b"an earlier message");
+    mac.reset();
+    mac.update(message);
+    mac.finalize()
+}
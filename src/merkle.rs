@@ -0,0 +1,544 @@
+//! Merkle trees as used by Certificate Transparency (RFC 6962, RFC 9162):
+//! leaves and inner nodes are hashed with different prefixes, so a leaf can
+//! never be passed off as a node. Proofs list the sibling hashes from the
+//! bottom of the tree up.
+
+use crate::hash::{Blake2b, HashFunction};
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// HASH(0x00 || data)
+pub fn leaf_hash<H: HashFunction + Clone>(hasher: &H, data: &[u8]) -> Vec<u8> {
+    let mut hasher = hasher.clone();
+    hasher.update(&[LEAF_PREFIX]);
+    hasher.update(data);
+    hasher.digest()
+}
+
+/// HASH(0x01 || left || right)
+fn node_hash<H: HashFunction + Clone>(hasher: &H, left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut hasher = hasher.clone();
+    hasher.update(&[NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.digest()
+}
+
+/// The largest power of two smaller than n, where the tree is split.
+fn split(n: usize) -> usize {
+    debug_assert!(n > 1);
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+/// An append-only list of entries and the Merkle tree over them. The hashes
+/// of the complete subtrees are kept as the entries come in, so roots and
+/// proofs cost O(log n) hashes.
+pub struct MerkleTree<H: HashFunction + Clone = Blake2b> {
+    // fresh hasher that gets cloned for every hash
+    hasher: H,
+    // levels[h][i] is the root of the complete subtree over the entries
+    // i * 2^h to (i + 1) * 2^h, levels[0] holds the leaf hashes
+    levels: Vec<Vec<Vec<u8>>>,
+}
+
+impl MerkleTree<Blake2b> {
+    pub fn new() -> Self {
+        Self::with_hasher(Blake2b::new(64))
+    }
+}
+
+impl Default for MerkleTree<Blake2b> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: HashFunction + Clone> MerkleTree<H> {
+    /// A tree that hashes with (clones of) hasher, which may carry settings
+    /// like the output length.
+    pub fn with_hasher(hasher: H) -> Self {
+        Self {
+            hasher,
+            levels: vec![Vec::new()],
+        }
+    }
+
+    /// Appends an entry and returns its index.
+    pub fn push(&mut self, data: &[u8]) -> usize {
+        self.levels[0].push(leaf_hash(&self.hasher, data));
+
+        // every subtree the entry completes, from the bottom up
+        let mut level = 0;
+        while self.levels[level].len() % 2 == 0 {
+            let [left, right] = &self.levels[level][self.levels[level].len() - 2..] else {
+                unreachable!("at least two nodes");
+            };
+            let parent = node_hash(&self.hasher, left, right);
+            if self.levels.len() == level + 1 {
+                self.levels.push(Vec::new());
+            }
+            self.levels[level + 1].push(parent);
+            level += 1;
+        }
+
+        self.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// The leaf hash of the entry at index.
+    pub fn leaf(&self, index: usize) -> &[u8] {
+        &self.levels[0][index]
+    }
+
+    /// The tree head over all entries.
+    pub fn root(&self) -> Vec<u8> {
+        self.root_at(self.len())
+    }
+
+    /// The tree head back when the tree had size entries.
+    pub fn root_at(&self, size: usize) -> Vec<u8> {
+        assert!(size <= self.len(), "the tree is smaller than {size}");
+        if size == 0 {
+            return self.hasher.clone().digest();
+        }
+        self.subtree(0, size)
+    }
+
+    /// The root over the entries start to start + len. The ranges the
+    /// recursion reaches start at a multiple of len rounded up to a power of
+    /// two, so the complete ones are in levels.
+    fn subtree(&self, start: usize, len: usize) -> Vec<u8> {
+        if len.is_power_of_two() {
+            let level = len.trailing_zeros();
+            return self.levels[level as usize][start >> level].clone();
+        }
+        let k = split(len);
+        node_hash(
+            &self.hasher,
+            &self.subtree(start, k),
+            &self.subtree(start + k, len - k),
+        )
+    }
+
+    /// The audit path of the entry at index in the tree of the first size
+    /// entries.
+    pub fn inclusion_proof(&self, index: usize, size: usize) -> Vec<Vec<u8>> {
+        assert!(size <= self.len(), "the tree is smaller than {size}");
+        assert!(
+            index < size,
+            "index {index} is not in a tree of size {size}"
+        );
+
+        let mut proof = Vec::new();
+        self.path(index, 0, size, &mut proof);
+        proof
+    }
+
+    // m is relative to start
+    fn path(&self, m: usize, start: usize, len: usize, proof: &mut Vec<Vec<u8>>) {
+        if len <= 1 {
+            return;
+        }
+        let k = split(len);
+        if m < k {
+            self.path(m, start, k, proof);
+            proof.push(self.subtree(start + k, len - k));
+        } else {
+            self.path(m - k, start + k, len - k, proof);
+            proof.push(self.subtree(start, k));
+        }
+    }
+
+    /// Proves that the tree of the first old_size entries is a prefix of the
+    /// tree of the first new_size entries.
+    pub fn consistency_proof(&self, old_size: usize, new_size: usize) -> Vec<Vec<u8>> {
+        assert!(
+            new_size <= self.len(),
+            "the tree is smaller than {new_size}"
+        );
+        assert!(
+            old_size <= new_size,
+            "the old tree is larger than the new one"
+        );
+
+        let mut proof = Vec::new();
+        if old_size > 0 {
+            self.subproof(old_size, 0, new_size, true, &mut proof);
+        }
+        proof
+    }
+
+    // complete is true as long as the old tree is the left edge of leaves,
+    // its root is then known to the verifier and left out
+    fn subproof(
+        &self,
+        m: usize,
+        start: usize,
+        len: usize,
+        complete: bool,
+        proof: &mut Vec<Vec<u8>>,
+    ) {
+        if m == len {
+            if !complete {
+                proof.push(self.subtree(start, len));
+            }
+            return;
+        }
+        let k = split(len);
+        if m <= k {
+            self.subproof(m, start, k, complete, proof);
+            proof.push(self.subtree(start + k, len - k));
+        } else {
+            self.subproof(m - k, start + k, len - k, false, proof);
+            proof.push(self.subtree(start, k));
+        }
+    }
+}
+
+/// Checks an audit path from inclusion_proof() for the leaf hash at index,
+/// against the root of a tree with size entries.
+pub fn verify_inclusion<H: HashFunction + Clone>(
+    hasher: &H,
+    index: usize,
+    size: usize,
+    leaf: &[u8],
+    proof: &[Vec<u8>],
+    root: &[u8],
+) -> bool {
+    if index >= size {
+        return false;
+    }
+
+    // fnode and snode walk up from the leaf and the last leaf, the path
+    // element is a left sibling where fnode is a right child or the last
+    // node of its level
+    let mut fnode = index;
+    let mut snode = size - 1;
+    let mut r = leaf.to_vec();
+    for p in proof {
+        if snode == 0 {
+            return false;
+        }
+        if fnode & 1 == 1 || fnode == snode {
+            r = node_hash(hasher, p, &r);
+            if fnode & 1 == 0 {
+                // skip the levels where fnode had no right sibling
+                while fnode & 1 == 0 && fnode != 0 {
+                    fnode >>= 1;
+                    snode >>= 1;
+                }
+            }
+        } else {
+            r = node_hash(hasher, &r, p);
+        }
+        fnode >>= 1;
+        snode >>= 1;
+    }
+
+    snode == 0 && r == root
+}
+
+/// Checks a proof from consistency_proof() that the tree with root old_root
+/// and old_size entries is a prefix of the one with new_root and new_size.
+pub fn verify_consistency<H: HashFunction + Clone>(
+    hasher: &H,
+    old_size: usize,
+    new_size: usize,
+    old_root: &[u8],
+    new_root: &[u8],
+    proof: &[Vec<u8>],
+) -> bool {
+    if old_size > new_size {
+        return false;
+    }
+    if old_size == new_size {
+        return proof.is_empty() && old_root == new_root;
+    }
+    // the empty tree is a prefix of every tree
+    if old_size == 0 {
+        return proof.is_empty();
+    }
+    if proof.is_empty() {
+        return false;
+    }
+
+    // a complete old tree is a node of the new tree, its root was left out
+    let mut path = proof.iter().map(Vec::as_slice);
+    let first = if old_size.is_power_of_two() {
+        old_root
+    } else {
+        path.next().expect("not empty")
+    };
+
+    let mut fnode = old_size - 1;
+    let mut snode = new_size - 1;
+    while fnode & 1 == 1 {
+        fnode >>= 1;
+        snode >>= 1;
+    }
+
+    // fr rebuilds the old root, sr the new one
+    let mut fr = first.to_vec();
+    let mut sr = first.to_vec();
+    for c in path {
+        if snode == 0 {
+            return false;
+        }
+        if fnode & 1 == 1 || fnode == snode {
+            fr = node_hash(hasher, c, &fr);
+            sr = node_hash(hasher, c, &sr);
+            while fnode & 1 == 0 && fnode != 0 {
+                fnode >>= 1;
+                snode >>= 1;
+            }
+        } else {
+            sr = node_hash(hasher, &sr, c);
+        }
+        fnode >>= 1;
+        snode >>= 1;
+    }
+
+    fr == old_root && sr == new_root && snode == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hash::HashFunction,
+        hash::{Blake2b, Sha256},
+        merkle::{MerkleTree, leaf_hash, node_hash, split, verify_consistency, verify_inclusion},
+        slice::u8_to_hexstr,
+    };
+
+    // the leaves of the certificate-transparency-go test tree
+    fn ct_tree() -> MerkleTree<Sha256> {
+        let leaves: [&[u8]; 8] = [
+            b"",
+            b"\x00",
+            b"\x10",
+            b"\x20\x21",
+            b"\x30\x31",
+            b"\x40\x41\x42\x43",
+            b"\x50\x51\x52\x53\x54\x55\x56\x57",
+            b"\x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f",
+        ];
+
+        let mut tree = MerkleTree::with_hasher(Sha256::new());
+        for leaf in leaves {
+            tree.push(leaf);
+        }
+        tree
+    }
+
+    fn hex(proof: &[Vec<u8>]) -> Vec<String> {
+        proof.iter().map(|p| u8_to_hexstr(p)).collect()
+    }
+
+    #[test]
+    fn ct_roots() {
+        let tree = ct_tree();
+        let roots = [
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+            "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+            "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+            "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+            "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+            "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+            "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+            "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+        ];
+
+        for (size, expected) in roots.iter().enumerate() {
+            assert_eq!(u8_to_hexstr(&tree.root_at(size)), *expected);
+        }
+        assert_eq!(tree.root(), tree.root_at(8));
+    }
+
+    #[test]
+    fn ct_inclusion_proofs() {
+        let tree = ct_tree();
+        let cases: [(usize, usize, &[&str]); 4] = [
+            (
+                0,
+                8,
+                &[
+                    "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+                    "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+                    "6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4",
+                ],
+            ),
+            (
+                5,
+                8,
+                &[
+                    "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+                    "ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+                    "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+                ],
+            ),
+            (
+                2,
+                3,
+                &["fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125"],
+            ),
+            (
+                1,
+                5,
+                &[
+                    "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+                    "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+                    "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+                ],
+            ),
+        ];
+
+        for (index, size, expected) in cases {
+            let proof = tree.inclusion_proof(index, size);
+            assert_eq!(hex(&proof), expected);
+            assert!(verify_inclusion(
+                &Sha256::new(),
+                index,
+                size,
+                tree.leaf(index),
+                &proof,
+                &tree.root_at(size)
+            ));
+        }
+    }
+
+    #[test]
+    fn ct_consistency_proofs() {
+        let tree = ct_tree();
+        let cases: [(usize, usize, &[&str]); 4] = [
+            (
+                1,
+                8,
+                &[
+                    "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+                    "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+                    "6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4",
+                ],
+            ),
+            (
+                6,
+                8,
+                &[
+                    "0ebc5d3437fbe2db158b9f126a1d118e308181031d0a949f8dededebc558ef6a",
+                    "ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+                    "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+                ],
+            ),
+            (
+                2,
+                5,
+                &[
+                    "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+                    "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+                ],
+            ),
+            (
+                4,
+                7,
+                &["837dbb152e9b079010717e84e865da4ebc0fa198a806d59d31bf15accef22d0e"],
+            ),
+        ];
+
+        for (old_size, new_size, expected) in cases {
+            let proof = tree.consistency_proof(old_size, new_size);
+            assert_eq!(hex(&proof), expected);
+            assert!(verify_consistency(
+                &Sha256::new(),
+                old_size,
+                new_size,
+                &tree.root_at(old_size),
+                &tree.root_at(new_size),
+                &proof
+            ));
+        }
+    }
+
+    #[test]
+    fn merkle_cached_roots() {
+        // the recursion of RFC 9162 section 2.1.1 over the leaf hashes
+        fn root(leaves: &[Vec<u8>]) -> Vec<u8> {
+            match leaves.len() {
+                0 => Blake2b::new(64).digest(),
+                1 => leaves[0].clone(),
+                n => {
+                    let k = split(n);
+                    node_hash(&Blake2b::new(64), &root(&leaves[..k]), &root(&leaves[k..]))
+                }
+            }
+        }
+
+        let mut tree = MerkleTree::default();
+        let mut leaves = Vec::new();
+        for i in 0..70u8 {
+            assert_eq!(tree.root(), root(&leaves));
+            tree.push(&[i]);
+            leaves.push(tree.leaf(i as usize).to_vec());
+        }
+        for size in 0..=leaves.len() {
+            assert_eq!(tree.root_at(size), root(&leaves[..size]));
+        }
+    }
+
+    #[test]
+    fn merkle_all_proofs() {
+        let hasher = Blake2b::new(64);
+        let mut tree = MerkleTree::new();
+        for i in 0..20u8 {
+            assert_eq!(tree.push(&[i; 3]), i as usize);
+        }
+        assert_eq!(tree.leaf(3), leaf_hash(&hasher, &[3; 3]));
+
+        for size in 1..=tree.len() {
+            let root = tree.root_at(size);
+            for index in 0..size {
+                let leaf = tree.leaf(index);
+                let proof = tree.inclusion_proof(index, size);
+                assert!(verify_inclusion(&hasher, index, size, leaf, &proof, &root));
+
+                // a wrong leaf or a tampered path
+                let other = tree.leaf((index + 1) % tree.len());
+                assert!(!verify_inclusion(
+                    &hasher, index, size, other, &proof, &root
+                ));
+                if let Some(last) = proof.len().checked_sub(1) {
+                    let mut bad = proof.clone();
+                    bad[last][0] ^= 1;
+                    assert!(!verify_inclusion(&hasher, index, size, leaf, &bad, &root));
+                }
+            }
+
+            for old_size in 0..=size {
+                let old_root = tree.root_at(old_size);
+                let proof = tree.consistency_proof(old_size, size);
+                assert!(verify_consistency(
+                    &hasher, old_size, size, &old_root, &root, &proof
+                ));
+
+                if old_size > 0 && old_size < size {
+                    let mut bad = proof.clone();
+                    bad[0][0] ^= 1;
+                    assert!(!verify_consistency(
+                        &hasher, old_size, size, &old_root, &root, &bad
+                    ));
+                    // a root that is not the one of the old tree
+                    assert!(!verify_consistency(
+                        &hasher, old_size, size, &root, &root, &proof
+                    ));
+                }
+            }
+        }
+    }
+}
@@ -1,19 +1,3 @@
-#[inline]
-pub(crate) fn pad_trailing(slice: &mut Vec<u8>, pad_with: u8, pad_to_len: usize) {
-    if slice.len() > pad_to_len {
-        return;
-    }
-
-    for _ in slice.len()..pad_to_len {
-        slice.push(pad_with);
-    }
-}
-
-#[inline]
-pub(crate) fn prepend(slice: &mut Vec<u8>, mut prepend_with: Vec<u8>) {
-    slice.splice(..0, prepend_with.drain(..));
-}
-
 #[inline]
 pub(crate) fn u8_to_hexstr(slice: &[u8]) -> String {
     slice
@@ -28,3 +12,13 @@ pub(crate) fn decode_hex(s: &str) -> Vec<u8> {
         .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
         .collect()
 }
+
+/// Compares two slices without returning early on the first differing byte,
+/// so the time taken does not leak where a MAC tag goes wrong.
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}